@@ -1,16 +1,22 @@
-use helpers::{print_current_dir, AocResult};
+use helpers::{input_path, print_current_dir, AocResult, Solution};
 use itertools::Itertools;
-use std::ops::Add;
+use std::collections::VecDeque;
+use std::iter::Sum;
+use std::ops::{Add, Sub};
 
-fn main() -> AocResult<()> {
-    print_current_dir();
-    let input: Vec<usize> = helpers::read_lines_parse("day1/day1.input")?;
+struct Day1;
+
+impl Solution for Day1 {
+    type Input = Vec<usize>;
+
+    fn parse(s: &str) -> AocResult<Self::Input> {
+        s.lines().map(|l| Ok(l.parse()?)).collect()
+    }
 
     // count the number of times a depth measurement increases from the previous measurement
-    println!(
-        "Positive difference count: {}",
-        count_positive_differences(input.iter())
-    );
+    fn part1(input: &Self::Input) -> String {
+        count_positive_differences(input.iter()).to_string()
+    }
 
     // Considering every single measurement isn't as useful as you expected: there's just too
     // much noise in the data.
@@ -18,13 +24,35 @@ fn main() -> AocResult<()> {
     // Instead, consider sums of a three-measurement sliding window.
     // Your goal now is to count the number of times the sum of measurements in this sliding window
     // increases from the previous sum.
-    let tripplet_sums = sum_windows_of_three(input.into_iter());
-    println!(
-        "Positive difference of windows of three count: {}",
-        count_positive_differences(tripplet_sums)
-    );
+    fn part2(input: &Self::Input) -> String {
+        count_window_increases(input.iter().copied(), 3).to_string()
+    }
+
+    // characterize the whole depth profile
+    fn details(input: &Self::Input) -> Vec<String> {
+        let increases = increase_indices(input.iter());
+        vec![
+            format!(
+                "Decreases: {}, net change: {}",
+                count_negative_differences(input.iter()),
+                net_change(input.iter().map(|&depth| depth as i64))
+            ),
+            format!(
+                "First increase at measurement {:?}, last at {:?}",
+                increases.first(),
+                increases.last()
+            ),
+            format!(
+                "Largest sum of three measurements: {:?}",
+                sum_windows_of_three(input.iter().copied()).max()
+            ),
+        ]
+    }
+}
 
-    Ok(())
+fn main() -> AocResult<()> {
+    print_current_dir();
+    Day1::run(input_path("day1", "day1.input"))
 }
 
 fn count_positive_differences<T: PartialOrd + Clone>(i: impl Iterator<Item = T>) -> usize {
@@ -33,17 +61,91 @@ fn count_positive_differences<T: PartialOrd + Clone>(i: impl Iterator<Item = T>)
         .count()
 }
 
-fn sum_windows_of_three<T: Add<Output = T> + Clone>(
+// positions of the measurements that are larger than their predecessor, counting from 1
+fn increase_indices<T: PartialOrd + Clone>(i: impl Iterator<Item = T>) -> Vec<usize> {
+    i.tuple_windows::<(_, _)>()
+        .enumerate()
+        .filter(|(_, (first, second))| second > first)
+        // window n ends in the measurement at 0-based position n + 1
+        .map(|(n, _)| n + 2)
+        .collect()
+}
+
+fn count_negative_differences<T: PartialOrd + Clone>(i: impl Iterator<Item = T>) -> usize {
+    i.tuple_windows::<(_, _)>()
+        .filter(|(first, second)| second < first)
+        .count()
+}
+
+// signed sum of all consecutive deltas, which amounts to last minus first
+fn net_change<T: Sub<Output = T> + Sum + Clone>(i: impl Iterator<Item = T>) -> T {
+    i.tuple_windows::<(_, _)>()
+        .map(|(first, second)| second - first)
+        .sum()
+}
+
+// adjacent windows share all but one element, so a window sum increases exactly when the
+// element entering the window is larger than the one leaving it
+// only the last window + 1 elements are kept, no sums are built
+fn count_window_increases<T: Add<Output = T> + PartialOrd + Copy>(
+    i: impl Iterator<Item = T>,
+    window: usize,
+) -> usize {
+    if window == 0 {
+        return 0;
+    }
+    let mut buffer = VecDeque::with_capacity(window + 1);
+    let mut increases = 0;
+    for value in i {
+        buffer.push_back(value);
+        if buffer.len() > window {
+            let leaving = buffer
+                .pop_front()
+                .expect("Buffer holds window + 1 elements");
+            if value > leaving {
+                increases += 1;
+            }
+        }
+    }
+    increases
+}
+
+fn sum_windows_of_three<T: Add<Output = T> + Copy>(
+    i: impl Iterator<Item = T>,
+) -> impl Iterator<Item = T> {
+    sum_windows_of_n(i, 3)
+}
+
+// yields the sum of every contiguous window of n elements, nothing if there are less than n
+fn sum_windows_of_n<T: Add<Output = T> + Copy>(
     i: impl Iterator<Item = T>,
+    n: usize,
 ) -> impl Iterator<Item = T> {
-    i.tuple_windows::<(_, _, _)>().map(|w| w.0 + w.1 + w.2)
+    let mut window = VecDeque::with_capacity(n);
+    i.filter_map(move |value| {
+        if n == 0 {
+            return None;
+        }
+        if window.len() == n {
+            window.pop_front();
+        }
+        window.push_back(value);
+        if window.len() == n {
+            window.iter().copied().reduce(|sum, v| sum + v)
+        } else {
+            None
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use helpers::read_file_string;
 
     static EXAMPLE_NUMBERS: [i32; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+    static EXAMPLE_INPUT: &str = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263";
+
     #[test]
     fn example_day1() {
         let differences = count_positive_differences(EXAMPLE_NUMBERS.iter());
@@ -59,4 +161,95 @@ mod tests {
 
         assert_eq!(differences, 5)
     }
+
+    #[test]
+    fn example_window_increases() {
+        let increases = count_window_increases(EXAMPLE_NUMBERS.iter().copied(), 3);
+
+        assert_eq!(increases, 5);
+        let expected = [(0, 0), (1, 7), (2, 5), (4, 6), (5, 5), (10, 0), (11, 0)];
+        for (window, increases) in expected {
+            assert_eq!(
+                count_window_increases(EXAMPLE_NUMBERS.iter().copied(), window),
+                increases,
+                "window {}",
+                window
+            );
+        }
+        assert_eq!(count_window_increases([1, 2].iter().copied(), 3), 0);
+    }
+
+    #[test]
+    fn example_increase_indices() {
+        // 199 (N/A - no previous measurement)
+        // 200 (increased)
+        // 208 (increased)
+        // 210 (increased)
+        // 200 (decreased)
+        // 207 (increased)
+        // 240 (increased)
+        // 269 (increased)
+        // 260 (decreased)
+        // 263 (increased)
+        let indices = increase_indices(EXAMPLE_NUMBERS.iter());
+
+        assert_eq!(indices, [2, 3, 4, 6, 7, 8, 10]);
+    }
+
+    #[test]
+    fn short_inputs() {
+        let empty: [i32; 0] = [];
+
+        assert_eq!(count_positive_differences(empty.iter()), 0);
+        assert_eq!(count_positive_differences([1].iter()), 0);
+        assert_eq!(sum_windows_of_three(empty.iter().copied()).count(), 0);
+        assert_eq!(sum_windows_of_three([1].iter().copied()).count(), 0);
+        assert_eq!(sum_windows_of_three([1, 2].iter().copied()).count(), 0);
+    }
+
+    #[test]
+    fn example_decreases_and_net_change() {
+        let decreases = count_negative_differences(EXAMPLE_NUMBERS.iter());
+        let change = net_change(EXAMPLE_NUMBERS.iter().copied());
+
+        assert_eq!(decreases, 2);
+        assert_eq!(change, 263 - 199);
+    }
+
+    #[test]
+    fn sum_windows_of_arbitrary_size() {
+        let of_three: Vec<_> = sum_windows_of_n(EXAMPLE_NUMBERS.iter().copied(), 3).collect();
+        let tripplets: Vec<_> = EXAMPLE_NUMBERS
+            .iter()
+            .tuple_windows::<(_, _, _)>()
+            .map(|w| w.0 + w.1 + w.2)
+            .collect();
+        let of_one: Vec<_> = sum_windows_of_n(EXAMPLE_NUMBERS.iter().copied(), 1).collect();
+        let oversized = sum_windows_of_n(EXAMPLE_NUMBERS.iter().copied(), 11).count();
+        let empty_window = sum_windows_of_n(EXAMPLE_NUMBERS.iter().copied(), 0).count();
+
+        assert_eq!(of_three, tripplets);
+        assert_eq!(of_one, EXAMPLE_NUMBERS);
+        assert_eq!(oversized, 0);
+        assert_eq!(empty_window, 0);
+    }
+
+    #[test]
+    fn example_from_input_path() {
+        let path = input_path("day1", "day1.testinput");
+
+        let input = Day1::parse(&read_file_string(&path).unwrap()).unwrap();
+
+        assert!(path.exists());
+        assert_eq!(input.len(), 10);
+        assert_eq!(Day1::part1(&input), "7");
+    }
+
+    #[test]
+    fn example_solution() {
+        let input = Day1::parse(EXAMPLE_INPUT).unwrap();
+
+        assert_eq!(Day1::part1(&input), "7");
+        assert_eq!(Day1::part2(&input), "5");
+    }
 }
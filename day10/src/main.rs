@@ -1,13 +1,61 @@
-use helpers::AocResult;
+use helpers::{AocError, AocResult};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum Line<'a> {
     Empty,
-    Corrupted(&'a str, OpeningToken, char),
+    // the last field is the 0-based index of the illegal char
+    Corrupted(&'a str, OpeningToken, char, usize),
     Incomplete(&'a str, Vec<OpeningToken>),
     Complete(&'a str),
 }
 
+impl Display for Line<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Line::Empty => write!(f, "Empty"),
+            Line::Corrupted(_, expected, found, idx) => write!(
+                f,
+                "Corrupted at {}: expected {} found {}",
+                idx,
+                closing_char(*expected),
+                found
+            ),
+            Line::Incomplete(_, open) => write!(f, "Incomplete, needs {}", completion_string(open)),
+            Line::Complete(s) => write!(f, "Complete: {}", s),
+        }
+    }
+}
+
+// Line without borrowing the input, Corrupted and Incomplete carry their score
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LineKind {
+    Empty,
+    Complete,
+    Corrupted(usize),
+    Incomplete(usize),
+}
+
+impl TryFrom<&Line<'_>> for LineKind {
+    type Error = AocError;
+
+    fn try_from(line: &Line<'_>) -> Result<Self, Self::Error> {
+        match line {
+            Line::Empty => Ok(LineKind::Empty),
+            Line::Complete(_) => Ok(LineKind::Complete),
+            Line::Corrupted(_, _, invalid_char @ (')' | ']' | '}' | '>'), _) => {
+                Ok(LineKind::Corrupted(illegal_points(*invalid_char)))
+            }
+            Line::Corrupted(_, _, invalid_char, idx) => Err(AocError::ParseStructError(format!(
+                "Invalid character '{}' at {}",
+                invalid_char, idx
+            ))),
+            Line::Incomplete(_, open) => Ok(LineKind::Incomplete(autocomplete_score(open))),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 enum OpeningToken {
     Round,
@@ -21,40 +69,53 @@ fn main() -> AocResult<()> {
 
     let lines: Vec<_> = input.lines().map(|l| parse_line(l)).collect();
 
-    // Part 1
-    let score: usize = lines
-        .iter()
-        .filter_map(|l| match l {
-            Line::Corrupted(_, _, invalid_char) => Some(illegal_points(*invalid_char)),
-            _ => None,
-        })
-        .sum();
+    let (corruption_score, autocomplete_score) = score_lines(&lines)?;
 
-    println!("Corruption Score is {}", score);
+    // Part 1
+    println!("Corruption Score is {}", corruption_score);
 
     // Part 2
-    let mut incomplete_scores: Vec<_> = lines
-        .iter()
-        .filter_map(|l| match l {
-            Line::Incomplete(_, open) => Some(autocomplete_score(open)),
-            _ => None,
-        })
-        .collect();
+    println!("Autocomplete center score is {}", autocomplete_score);
+
+    Ok(())
+}
+
+// checks one line at a time, without reading everything into memory
+#[cfg(test)]
+fn check_lines<R: std::io::BufRead>(
+    reader: R,
+) -> impl Iterator<Item = AocResult<(String, LineKind)>> {
+    reader.lines().map(|line| {
+        let line = line?;
+        let kind = LineKind::try_from(&parse_line(&line))?;
+        Ok((line, kind))
+    })
+}
+
+// (corruption score, middle autocomplete score), the latter is 0 if no line is incomplete
+// fails on the first line with a character that is no bracket
+fn score_lines(lines: &[Line]) -> AocResult<(usize, usize)> {
+    let mut corruption_score = 0;
+    let mut incomplete_scores = Vec::new();
+    for line in lines {
+        match LineKind::try_from(line)? {
+            LineKind::Corrupted(score) => corruption_score += score,
+            LineKind::Incomplete(score) => incomplete_scores.push(score),
+            LineKind::Empty | LineKind::Complete => {}
+        }
+    }
+
     // Autocomplete tools are an odd bunch: the winner is found by sorting all of the scores and
     // then taking the middle score. (There will always be an odd number of scores to consider.)
     // In this example, the middle score is 288957 because there are the same number of scores
     // smaller and larger than it.
     incomplete_scores.sort_unstable();
+    let middle_score = incomplete_scores
+        .get(incomplete_scores.len() / 2)
+        .copied()
+        .unwrap_or(0);
 
-    let middle = incomplete_scores.len() / 2;
-    println!(
-        "Autocomplete center score {} of {} is {}",
-        middle,
-        incomplete_scores.len(),
-        incomplete_scores[middle]
-    );
-
-    Ok(())
+    Ok((corruption_score, middle_score))
 }
 
 fn closing_char(c: OpeningToken) -> char {
@@ -75,20 +136,20 @@ fn opening_token(c: char) -> Option<OpeningToken> {
     }
 }
 
-fn parse_line(s: &str) -> Line {
-    let mut chars = s.chars();
-    if let Some(first_char) = chars.next() {
+fn parse_line(s: &str) -> Line<'_> {
+    let mut chars = s.chars().enumerate();
+    if let Some((_, first_char)) = chars.next() {
         if let Some(first_token) = opening_token(first_char) {
             // let mut root = Token::new(first_token);
             let mut open: Vec<_> = vec![first_token];
-            for c in chars {
+            for (idx, c) in chars {
                 if let Some(opening) = opening_token(c) {
                     open.push(opening)
                 } else if let Some(last) = open.last() {
                     if c == closing_char(*last) {
                         open.pop();
                     } else {
-                        return Line::Corrupted(s, *last, c);
+                        return Line::Corrupted(s, *last, c, idx);
                     }
                 }
             }
@@ -138,6 +199,11 @@ fn completion_points(t: OpeningToken) -> usize {
     }
 }
 
+// the closing characters in the order they have to be appended
+fn completion_string(open: &[OpeningToken]) -> String {
+    open.iter().rev().map(|&t| closing_char(t)).collect()
+}
+
 fn autocomplete_score(open: &[OpeningToken]) -> usize {
     // start from the back, as they are the first ones to be closed
     open.iter()
@@ -148,6 +214,7 @@ fn autocomplete_score(open: &[OpeningToken]) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Cursor;
 
     #[test]
     fn parse_tokens_ok() {
@@ -185,6 +252,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn corrupted_position() {
+        // {([(<{}[<>[]}>{[]{[(<()> - Expected ], but found } instead.
+        let line = parse_line("{([(<{}[<>[]}>{[]{[(<()>");
+        let short = parse_line("{]");
+
+        assert_eq!(
+            line,
+            Line::Corrupted("{([(<{}[<>[]}>{[]{[(<()>", OpeningToken::Square, '}', 12)
+        );
+        assert!(matches!(
+            short,
+            Line::Corrupted(_, OpeningToken::Curly, ']', 1)
+        ));
+    }
+
+    #[test]
+    fn display_lines() {
+        let corrupted = parse_line("{([(<{}[<>[]}>{[]{[(<()>");
+        let incomplete = parse_line("[({(<(())[]>[[{[]{<()<>>");
+
+        assert_eq!(corrupted.to_string(), "Corrupted at 12: expected ] found }");
+        assert_eq!(incomplete.to_string(), "Incomplete, needs }}]])})]");
+        assert_eq!(parse_line("[]").to_string(), "Complete: []");
+        assert_eq!(parse_line("").to_string(), "Empty");
+    }
+
     #[test]
     fn parse_tokens_incomplete() {
         let incomplete_tokens = ["{", "{([(<{}[<", "[[<[([])]<([[{"];
@@ -216,7 +310,7 @@ mod tests {
         let invalid_chars: Vec<_> = results
             .iter()
             .filter_map(|l| match l {
-                Line::Corrupted(_, _, invalid_char) => Some(invalid_char),
+                Line::Corrupted(_, _, invalid_char, _) => Some(invalid_char),
                 _ => None,
             })
             .collect();
@@ -260,4 +354,67 @@ mod tests {
         assert_eq!(incomplete_lines.len(), 5);
         assert_eq!(scores, [288957, 5566, 1480781, 995444, 294]);
     }
+
+    #[test]
+    fn example_scores() {
+        let lines: Vec<_> = TEST_INPUT.iter().map(|t| parse_line(t)).collect();
+
+        assert_eq!(score_lines(&lines).unwrap(), (26397, 288957));
+        assert_eq!(score_lines(&lines[2..3]).unwrap(), (1197, 0));
+        assert_eq!(score_lines(&[]).unwrap(), (0, 0));
+        assert!(matches!(
+            score_lines(&[parse_line("[]"), parse_line("(x")]),
+            Err(AocError::ParseStructError(_))
+        ));
+    }
+
+    #[test]
+    fn example_streaming() {
+        let text = format!("{}\n\n{}", TEST_INPUT[..3].join("\n"), "[]");
+
+        let checked: Vec<_> = check_lines(Cursor::new(text))
+            .collect::<AocResult<_>>()
+            .unwrap();
+        let kinds: Vec<_> = checked.iter().map(|(_, kind)| *kind).collect();
+
+        assert_eq!(checked[1].0, TEST_INPUT[1]);
+        assert_eq!(
+            kinds,
+            [
+                LineKind::Incomplete(288957),
+                LineKind::Incomplete(5566),
+                LineKind::Corrupted(1197),
+                LineKind::Empty,
+                LineKind::Complete
+            ]
+        );
+    }
+
+    #[test]
+    fn streaming_invalid_character() {
+        let mut checked = check_lines(Cursor::new("[]\n(x\n{}"));
+
+        assert!(matches!(checked.next(), Some(Ok((_, LineKind::Complete)))));
+        assert!(matches!(
+            checked.next(),
+            Some(Err(AocError::ParseStructError(_)))
+        ));
+        assert!(matches!(checked.next(), Some(Ok((_, LineKind::Complete)))));
+    }
+
+    #[test]
+    fn example_completion_strings() {
+        let completions: Vec<_> = TEST_INPUT
+            .iter()
+            .filter_map(|t| match parse_line(t) {
+                Line::Incomplete(_, open) => Some(completion_string(&open)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            completions,
+            ["}}]])})]", ")}>]})", "}}>}>))))", "]]}}]}]}>", "])}>"]
+        );
+    }
 }
@@ -1,74 +1,74 @@
 use helpers::{read_file_string, AocError, AocResult, Grid};
+use std::iter::repeat_with;
 use std::str::FromStr;
 
 type Unit = u32;
-const FLASH_THRESHOLD: u32 = 9;
+const FLASH_THRESHOLD: Unit = 9;
 
 #[derive(Debug)]
 struct Octopusses {
     state: Grid<Unit>,
+    // octopusses with an energy level greater than this flash
+    threshold: Unit,
+    // how often each octopus flashed during all steps so far
+    flash_counts: Grid<usize>,
 }
 
 impl Octopusses {
-    // returns the number of flashes that ocurred during this step
-    fn flash(&mut self, x: usize, y: usize) {
-        let middle = self
-            .state
-            .get_mut(x, y)
-            .expect("Flashed Items must be inside dimensions");
-        // any octopus that flashed during this step has its energy level set to 0,
-        // as it used all of its energy to flash.
-        *middle.value = 0;
-        for (x, y) in self.state.surrounding_indexes(x, y) {
-            let surrounding_octopus = self.state.get_mut(x, y).unwrap();
-            // The only octopusses that are 0 have already exploded a round and must be ignored
-            if *surrounding_octopus.value > 0 {
-                *surrounding_octopus.value += 1
-            }
+    fn new(state: Grid<Unit>) -> Self {
+        Self::with_threshold(state, FLASH_THRESHOLD)
+    }
+
+    fn with_threshold(state: Grid<Unit>, threshold: Unit) -> Self {
+        let (x_dim, y_dim) = state.dimensions();
+        Octopusses {
+            state,
+            threshold,
+            flash_counts: Grid::default(x_dim, y_dim),
         }
     }
+
+    // returns the number of flashes that ocurred during this step
     fn step(&mut self) -> usize {
         // First, the energy level of each octopus increases by 1.
-        // After this step it is impossible to have octopusses with 0 energy.
-        for octopus in self.state.iter_mut() {
-            *octopus += 1
-        }
-
         // Then, any octopus with an energy level greater than 9 flashes.
         // This increases the energy level of all adjacent octopuses by 1, including octopuses that
         // are diagonally adjacent. If this causes an octopus to have an energy level greater than 9,
         // it also flashes. This process continues as long as new octopuses keep having their energy
         // level increased beyond 9. (An octopus can only flash at most once per step.)
-        let mut flashes = 0;
-        let (x_max, y_max) = self.state.dimensions();
-        while self.state.iter().any(|p| *p > FLASH_THRESHOLD) {
-            for y in 0..y_max {
-                for x in 0..x_max {
-                    let value = self
-                        .state
-                        .get(x, y)
-                        .expect("Item must be inside valid dimensions");
-                    if *value.value > FLASH_THRESHOLD {
-                        flashes += 1;
-                        self.flash(x, y)
-                    }
-                }
+        self.step_detailed().len()
+    }
+
+    // same as step, but returns the coordinates of the octopusses that flashed
+    fn step_detailed(&mut self) -> Vec<(usize, usize)> {
+        let flashed = self.state.step_threshold_points(self.threshold, true);
+        for &(x, y) in &flashed {
+            if let Some(count) = self.flash_counts.get_mut(x, y) {
+                *count.value += 1
             }
         }
-        flashes
+        flashed
+    }
+
+    #[cfg(test)]
+    fn flash_counts(&self) -> Grid<usize> {
+        self.flash_counts.clone()
+    }
+
+    // the number of flashes of every following step, never ends
+    fn steps(mut self) -> impl Iterator<Item = usize> {
+        repeat_with(move || self.step())
     }
 
     // returns the number of steps required to reach the synchronized flashing
-    fn step_until_synchronized_flash(&mut self) -> usize {
-        let mut steps = 0;
+    fn step_until_synchronized_flash(self) -> usize {
         let dimensions = self.state.dimensions();
         let octocount = dimensions.0 * dimensions.1;
-        let mut flashes = 0;
-        while flashes != octocount {
-            steps += 1;
-            flashes = self.step();
-        }
-        steps
+        // steps are counted from 1
+        self.steps()
+            .take_while(|&flashes| flashes != octocount)
+            .count()
+            + 1
     }
 }
 
@@ -89,7 +89,7 @@ impl FromStr for Octopusses {
             .collect();
 
         let state = Grid::from_slice(&numbers, x_length)?;
-        Ok(Octopusses { state })
+        Ok(Octopusses::new(state))
     }
 }
 
@@ -98,16 +98,14 @@ fn main() -> AocResult<()> {
     // Part 1
     // Given the starting energy levels of the dumbo octopuses in your cavern, simulate 100 steps.
     // How many total flashes are there after 100 steps?
-    let mut octopy1 = Octopusses::from_str(&input)?;
-    let flash_sum: usize = (0..100).map(|_| octopy1.step()).sum();
+    let flash_sum: usize = Octopusses::from_str(&input)?.steps().take(100).sum();
     println!("Number of flashes after 100 steps: {}", flash_sum);
 
     // Part 2
     // If you can calculate the exact moments when the octopuses will all flash simultaneously, you
     // should be able to navigate through the cavern. What is the first step during which all
     // octopuses flash?
-    let mut octopy2 = Octopusses::from_str(&input)?;
-    let steps = octopy2.step_until_synchronized_flash();
+    let steps = Octopusses::from_str(&input)?.step_until_synchronized_flash();
     println!("The first synchronized flash happens at step: {}", steps);
 
     Ok(())
@@ -129,12 +127,83 @@ mod tests {
     }
 
     #[test]
-    fn example_part2() {
-        assert!(true);
+    fn example_generic_engine() {
+        let input = read_file_string("day11.testinput").unwrap();
+        let mut state = Octopusses::from_str(&input).unwrap().state;
+
+        let flash_sum: usize = (0..100)
+            .map(|_| state.step_threshold(FLASH_THRESHOLD, true))
+            .sum();
+
+        assert_eq!(flash_sum, 1656)
+    }
+
+    #[test]
+    fn example_flashing_points() {
+        let input = "11111\n19991\n19191\n19991\n11111";
+        let mut octopy = Octopusses::from_str(input).unwrap();
+
+        let mut first_step = octopy.step_detailed();
+        first_step.sort_unstable();
+        let second_step = octopy.step_detailed();
+
+        // After step 1:
+        // 34543
+        // 40004
+        // 50005
+        // 40004
+        // 34543
+        let expected: Vec<_> = (1..4).flat_map(|x| (1..4).map(move |y| (x, y))).collect();
+        assert_eq!(first_step, expected);
+        assert!(second_step.is_empty());
+    }
+
+    #[test]
+    fn other_threshold() {
+        let input = "11111\n19991\n19191\n19991\n11111";
+        let mut default = Octopusses::from_str(input).unwrap();
+        let state = Octopusses::from_str(input).unwrap().state;
+        let mut low = Octopusses::with_threshold(state, 5);
+
+        let default_flashes: Vec<_> = (0..2).map(|_| default.step()).collect();
+        let low_flashes: Vec<_> = (0..2).map(|_| low.step()).collect();
+
+        assert_eq!(default_flashes, [9, 0]);
+        assert_eq!(low_flashes, [9, 25]);
+    }
+
+    #[test]
+    fn example_steps_iterator() {
+        let input = read_file_string("day11.testinput").unwrap();
+        let octopy = || Octopusses::from_str(&input).unwrap();
+
+        let flash_sum: usize = octopy().steps().take(100).sum();
+        let synchronized = octopy().steps().position(|flashes| flashes == 100);
 
+        assert_eq!(flash_sum, 1656);
+        // steps are counted from 1
+        assert_eq!(synchronized.map(|step| step + 1), Some(195));
+    }
+
+    #[test]
+    fn example_flash_heatmap() {
         let input = read_file_string("day11.testinput").unwrap();
         let mut octopy = Octopusses::from_str(&input).unwrap();
 
+        for _ in 0..100 {
+            octopy.step();
+        }
+        let heatmap = octopy.flash_counts();
+
+        assert_eq!(heatmap.dimensions(), (10, 10));
+        assert_eq!(heatmap.iter().sum::<usize>(), 1656);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = read_file_string("day11.testinput").unwrap();
+        let octopy = Octopusses::from_str(&input).unwrap();
+
         // In the example above, the first time all octopuses flash simultaneously is step 195:
         let step_count = octopy.step_until_synchronized_flash();
 
@@ -1,11 +1,23 @@
 use helpers::{read_file_string, AocError, AocResult};
 use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 
 #[derive(Clone, Debug)]
 pub struct Edge<'a>(&'a str, &'a str);
 const EDGE_DELIMITER: char = '-';
 static START_NODE: &str = "start";
 static END_NODE: &str = "end";
+// visited small caves are tracked as bits of a u32
+const MAX_SMALL_CAVES: usize = u32::BITS as usize;
+
+// start and end are the only small caves with special rules
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CaveKind {
+    Start,
+    End,
+    Big,
+    Small,
+}
 
 impl<'a> TryFrom<&'a str> for Edge<'a> {
     type Error = AocError;
@@ -22,81 +34,175 @@ impl<'a> TryFrom<&'a str> for Edge<'a> {
     }
 }
 
+// owns its node names, so it doesn't depend on the lifetime of the parsed edges
 #[derive(Debug)]
-pub struct Graph<'a> {
-    neighbours: HashMap<String, Vec<&'a str>>,
+pub struct Graph {
+    neighbours: HashMap<String, Vec<String>>,
+    // bit mask of every small cave
+    small_caves: HashMap<String, u32>,
 }
 
-impl<'a> Graph<'a> {
-    pub fn with_edges(edges: &'a [Edge]) -> Self {
-        let mut neighbours = HashMap::new();
+impl FromStr for Graph {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let edges: Vec<_> = s.lines().map(Edge::try_from).collect::<AocResult<_>>()?;
+        Self::with_edges(&edges)
+    }
+}
+
+impl Graph {
+    pub fn with_edges(edges: &[Edge]) -> AocResult<Self> {
+        let mut neighbours: HashMap<String, Vec<String>> = HashMap::new();
         for edge in edges {
-            let entry = neighbours
-                .entry(edge.0.to_string())
-                .or_insert_with(Vec::new);
-            if !entry.contains(&edge.1) {
-                entry.push(edge.1)
+            for (from, to) in [(edge.0, edge.1), (edge.1, edge.0)] {
+                let entry = neighbours.entry(from.to_string()).or_default();
+                if !entry.iter().any(|n| n == to) {
+                    entry.push(to.to_string())
+                }
             }
-            let entry = neighbours
-                .entry(edge.1.to_string())
-                .or_insert_with(Vec::new);
-            if !entry.contains(&edge.0) {
-                entry.push(edge.0)
+        }
+        let mut small_caves = HashMap::new();
+        for node in edges.iter().flat_map(|edge| [edge.0, edge.1]) {
+            if Self::classify(node) != CaveKind::Big && !small_caves.contains_key(node) {
+                if small_caves.len() == MAX_SMALL_CAVES {
+                    return Err(AocError::ChallengeError(format!(
+                        "At most {} small caves are supported",
+                        MAX_SMALL_CAVES
+                    )));
+                }
+                small_caves.insert(node.to_string(), 1 << small_caves.len());
             }
         }
-        Self { neighbours }
+        Ok(Self {
+            neighbours,
+            small_caves,
+        })
+    }
+
+    // caves named in lowercase are small, all others are big
+    pub fn classify(node: &str) -> CaveKind {
+        if node == START_NODE {
+            CaveKind::Start
+        } else if node == END_NODE {
+            CaveKind::End
+        } else if node.chars().all(|c| c.is_ascii_lowercase()) {
+            CaveKind::Small
+        } else {
+            CaveKind::Big
+        }
+    }
+
+    // caves directly connected to node, in the order of the edges, unknown nodes have none
+    // names are owned by the graph, so they are returned as Strings
+    pub fn neighbours(&self, node: &str) -> &[String] {
+        self.neighbours.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    // whether end can be reached from start at all, ignoring how often caves may be visited
+    pub fn is_connected(&self) -> bool {
+        let mut seen = HashSet::from([START_NODE]);
+        let mut stack = vec![START_NODE];
+        while let Some(node) = stack.pop() {
+            if node == END_NODE {
+                return true;
+            }
+            for n in self.neighbours(node) {
+                if seen.insert(n) {
+                    stack.push(n)
+                }
+            }
+        }
+        false
     }
 
     // returns the numbers of distinct paths traversed
     pub fn traverse_visiting_single_caves_once(&self) -> usize {
-        self.traverse(START_NODE, HashSet::new(), false)
+        self.count_paths(0)
     }
 
     // returns the numbers of distinct paths traversed
     pub fn traverse_visiting_single_small_cave_twice(&self) -> usize {
-        self.traverse(START_NODE, HashSet::new(), true)
+        self.count_paths(1)
+    }
+
+    // returns the numbers of distinct paths traversed, where a single small cave may be
+    // revisited up to max_single_small_revisits times
+    pub fn count_paths(&self, max_single_small_revisits: usize) -> usize {
+        self.traverse(START_NODE, max_single_small_revisits)
+    }
+
+    // every distinct path from start to end, visiting small caves at most once
+    #[cfg(test)]
+    pub fn paths_visiting_small_caves_once(&self) -> Vec<Vec<&str>> {
+        let mut paths = Vec::new();
+        self.collect_paths(&mut vec![START_NODE], &mut paths);
+        paths
+    }
+
+    #[cfg(test)]
+    fn collect_paths<'a>(&'a self, path: &mut Vec<&'a str>, paths: &mut Vec<Vec<&'a str>>) {
+        let node = *path.last().expect("Paths are never empty");
+        if node == END_NODE {
+            paths.push(path.clone());
+            return;
+        }
+        for n in self.neighbours(node) {
+            let may_visit = match Self::classify(n) {
+                CaveKind::Big => true,
+                CaveKind::Start => false,
+                CaveKind::End | CaveKind::Small => !path.contains(&n.as_str()),
+            };
+            if may_visit {
+                path.push(n);
+                self.collect_paths(path, paths);
+                path.pop();
+            }
+        }
     }
 
     // big caves can be visited any number of times
-    // a single small cave can be visited at most twice
+    // a single small cave can be revisited revisits_left more times
     // and the remaining small caves can be visited at most once
     // However, the caves named start and end can only be visited exactly once each
-    fn traverse(
-        &'a self,
-        node: &'a str,
-        mut visited: HashSet<&'a str>,
-        allow_visiting_a_small_cave_twice: bool,
-    ) -> usize {
-        // We reached the end, this counts as a distinct path
-        if node == END_NODE {
-            1
-        } else {
-            visited.insert(node);
-            let neighbours = self.neighbours.get(node).expect("Node must exist");
-            let mut sum = 0;
-            for n in neighbours {
-                let is_small_cave = n.chars().all(|c| c.is_ascii_lowercase());
-
-                if visited.contains(n) && is_small_cave {
-                    if allow_visiting_a_small_cave_twice && n != &START_NODE {
-                        sum += self.traverse(n, visited.clone(), false)
+    // Depth first, with an explicit stack of (node, visited, revisited, revisits_left) frames
+    // visited only holds the bits of small caves, big caves are unconstrained
+    fn traverse(&self, node: &str, revisits_left: usize) -> usize {
+        let mut stack = vec![(node, 0, None, revisits_left)];
+        let mut sum = 0;
+        while let Some((node, mut visited, revisited, revisits_left)) = stack.pop() {
+            // We reached the end, this counts as a distinct path
+            if node == END_NODE {
+                sum += 1;
+                continue;
+            }
+            visited |= self.small_caves.get(node).copied().unwrap_or(0);
+            for n in self.neighbours(node) {
+                let small_cave_bit = self.small_caves.get(n).copied().unwrap_or(0);
+
+                if visited & small_cave_bit != 0 {
+                    let may_revisit = revisited.is_none_or(|r| r == n);
+                    let is_start = Self::classify(n) == CaveKind::Start;
+                    if revisits_left > 0 && may_revisit && !is_start {
+                        stack.push((n, visited, Some(n), revisits_left - 1))
                     }
                 } else {
-                    sum += self.traverse(n, visited.clone(), allow_visiting_a_small_cave_twice)
+                    stack.push((n, visited, revisited, revisits_left))
                 }
             }
-            sum
         }
+        sum
     }
 }
 
 fn main() -> AocResult<()> {
     let input = read_file_string("day12/day12.input")?;
-    let edges: Vec<_> = input
-        .lines()
-        .map(Edge::try_from)
-        .collect::<AocResult<_>>()?;
-    let graph = Graph::with_edges(&edges);
+    let graph: Graph = input.parse()?;
+    if !graph.is_connected() {
+        return Err(AocError::ChallengeError(
+            "There is no path from start to end".to_string(),
+        ));
+    }
     let distinct_paths = graph.traverse_visiting_single_caves_once();
     println!(
         "Distinct paths visiting small caves only once: {}",
@@ -122,12 +228,117 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_caves_once();
         assert_eq!(distinct_paths, 10);
     }
 
+    #[test]
+    fn example_path_counts() {
+        // input, paths visiting small caves once, with a single small cave twice, small caves
+        let examples = [
+            ("start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end", 10, 36, 5),
+            (
+                "dc-end\nHN-start\nstart-kj\ndc-start\ndc-HN\nLN-dc\nHN-end\nkj-sa\nkj-HN\nkj-dc",
+                19,
+                103,
+                5,
+            ),
+            (
+                "fs-end\nhe-DX\nfs-he\nstart-DX\npj-DX\nend-zg\nzg-sl\nzg-pj\npj-he\nRW-he\nfs-DX\npj-RW\nzg-RW\nstart-pj\nhe-WI\nzg-he\npj-fs\nstart-RW",
+                226,
+                3509,
+                7,
+            ),
+            // a can only be reached again through start or end, which are never revisited
+            ("start-a\na-end", 1, 1, 3),
+        ];
+
+        for (input, once, twice, small_caves) in examples {
+            let graph: Graph = input.parse().unwrap();
+            let paths = graph.paths_visiting_small_caves_once();
+
+            assert_eq!(graph.traverse_visiting_single_caves_once(), once);
+            assert_eq!(graph.traverse_visiting_single_small_cave_twice(), twice);
+            assert_eq!(graph.count_paths(0), once);
+            assert_eq!(graph.count_paths(1), twice);
+            assert!(graph.count_paths(2) >= twice);
+            assert_eq!(paths.len(), once);
+            assert!(paths
+                .iter()
+                .all(|p| p.first() == Some(&"start") && p.last() == Some(&"end")));
+            assert_eq!(graph.small_caves.len(), small_caves);
+        }
+
+        let example1: Graph = examples[0].0.parse().unwrap();
+        let paths = example1.paths_visiting_small_caves_once();
+        assert!(example1.count_paths(2) > 36);
+        assert!(paths.contains(&vec!["start", "A", "end"]));
+        assert!(paths.contains(&vec!["start", "A", "c", "A", "b", "A", "end"]));
+        assert_eq!(examples[3].0.parse::<Graph>().unwrap().count_paths(5), 1);
+        let example3: Graph = examples[2].0.parse().unwrap();
+        assert!(!example3.small_caves.contains_key("DX"));
+    }
+
+    #[test]
+    fn parse_graph() {
+        let input = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end";
+
+        let graph: Graph = input.parse().unwrap();
+        let missing_delimiter = "start-A\nstart b\nA-end".parse::<Graph>();
+
+        assert_eq!(graph.traverse_visiting_single_caves_once(), 10);
+        assert!(matches!(
+            missing_delimiter,
+            Err(AocError::ParseStructError(message)) if message.contains("start b")
+        ));
+    }
+
+    #[test]
+    fn too_many_small_caves() {
+        let names: Vec<_> = (1..=MAX_SMALL_CAVES).map(|i| "c".repeat(i)).collect();
+        let edges: Vec<_> = names.iter().map(|name| Edge(START_NODE, name)).collect();
+
+        assert!(matches!(
+            Graph::with_edges(&edges),
+            Err(AocError::ChallengeError(_))
+        ));
+        assert!(Graph::with_edges(&edges[1..]).is_ok());
+    }
+
+    #[test]
+    fn classify_caves() {
+        assert_eq!(Graph::classify("start"), CaveKind::Start);
+        assert_eq!(Graph::classify("end"), CaveKind::End);
+        assert_eq!(Graph::classify("A"), CaveKind::Big);
+        assert_eq!(Graph::classify("HN"), CaveKind::Big);
+        assert_eq!(Graph::classify("b"), CaveKind::Small);
+    }
+
+    #[test]
+    fn disconnected_graph() {
+        let example: Graph = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end"
+            .parse()
+            .unwrap();
+        let disconnected: Graph = "start-A\nA-b\nc-end\nc-D".parse().unwrap();
+
+        assert!(example.is_connected());
+        assert!(!disconnected.is_connected());
+        assert_eq!(disconnected.traverse_visiting_single_caves_once(), 0);
+    }
+
+    #[test]
+    fn example_neighbours() {
+        let graph: Graph = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end"
+            .parse()
+            .unwrap();
+
+        assert_eq!(graph.neighbours("start"), ["A", "b"]);
+        assert_eq!(graph.neighbours("d"), ["b"]);
+        assert!(graph.neighbours("unknown").is_empty());
+    }
+
     #[test]
     fn example_part1_example2() {
         let input =
@@ -137,7 +348,7 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_caves_once();
         assert_eq!(distinct_paths, 19);
@@ -152,7 +363,7 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_caves_once();
         assert_eq!(distinct_paths, 226);
@@ -166,7 +377,7 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_small_cave_twice();
         assert_eq!(distinct_paths, 36);
@@ -181,7 +392,7 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_small_cave_twice();
         assert_eq!(distinct_paths, 103);
@@ -196,7 +407,7 @@ mod tests {
             .map(Edge::try_from)
             .collect::<AocResult<_>>()
             .unwrap();
-        let graph = Graph::with_edges(&edges);
+        let graph = Graph::with_edges(&edges).unwrap();
 
         let distinct_paths = graph.traverse_visiting_single_small_cave_twice();
         assert_eq!(distinct_paths, 3509);
@@ -1,30 +1,11 @@
-use helpers::{read_file_string, AocError, AocResult, Grid};
+use helpers::{read_file_string, AocError, AocResult, Grid, Vec2};
+use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
 use std::str::FromStr;
 
 type Unit = usize;
 
-#[derive(Debug)]
-struct Point(Unit, Unit);
-
-//<x>,<y>
-const POINT_DELIM: char = ',';
-impl FromStr for Point {
-    type Err = AocError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y)) = s.split_once(POINT_DELIM) {
-            let x = x.parse()?;
-            let y = y.parse()?;
-            Ok(Point(x, y))
-        } else {
-            Err(AocError::ParseStructError(format!(
-                "Delimiter '{}' missing in Line '{}'",
-                POINT_DELIM, s
-            )))
-        }
-    }
-}
+type Point = Vec2<Unit>;
 
 #[derive(Debug, Copy, Clone)]
 enum Fold {
@@ -80,6 +61,66 @@ impl Debug for Dot {
     }
 }
 
+const LETTER_HEIGHT: usize = 6;
+// capital letters of the font the folded instructions are written in
+static LETTERS: [(char, &str); 18] = [
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+type LetterColumn = [bool; LETTER_HEIGHT];
+
+// glyphs are separated by empty columns, which also trims the padding of narrow letters
+fn split_glyphs(columns: &[LetterColumn]) -> Vec<String> {
+    columns
+        .split(|column| column.iter().all(|&marked| !marked))
+        .filter(|glyph| !glyph.is_empty())
+        .map(|glyph| {
+            (0..LETTER_HEIGHT)
+                .map(|y| {
+                    glyph
+                        .iter()
+                        .map(|column| if column[y] { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect()
+}
+
+fn decode_glyph(glyph: &str) -> Option<char> {
+    LETTERS.iter().find_map(|&(letter, pattern)| {
+        let rows: Vec<_> = pattern.lines().map(str::as_bytes).collect();
+        let columns: Vec<LetterColumn> = (0..rows[0].len())
+            .map(|x| {
+                let mut column = [false; LETTER_HEIGHT];
+                for (y, row) in rows.iter().enumerate() {
+                    column[y] = row[x] == b'#';
+                }
+                column
+            })
+            .collect();
+        (split_glyphs(&columns) == [glyph]).then_some(letter)
+    })
+}
+
 struct Paper {
     grid: Grid<Dot>,
 }
@@ -92,13 +133,13 @@ impl Debug for Paper {
 
 impl Paper {
     fn with_points(points: &[Point]) -> Option<Self> {
-        if let Some(max_x) = points.iter().map(|p| p.0).max() {
-            if let Some(max_y) = points.iter().map(|p| p.1).max() {
+        if let Some(max_x) = points.iter().map(|p| p.x).max() {
+            if let Some(max_y) = points.iter().map(|p| p.y).max() {
                 let grid = Grid::with_default(max_x + 1, max_y + 1, Dot::Empty);
                 let mut paper = Paper { grid };
                 for p in points {
                     // if starting point fails to mark, there is something fishy going on
-                    if !paper.mark(p.0, p.1) {
+                    if !paper.mark(p.x, p.y) {
                         return None;
                     }
                 }
@@ -120,54 +161,131 @@ impl Paper {
         }
     }
 
-    fn unmark(&mut self, x: usize, y: usize) -> bool {
-        if let Some(dot) = self.grid.get_mut(x, y) {
-            *dot.value = Dot::Empty;
-            true
-        } else {
-            false
+    // the fold line has to lie on the paper
+    fn check_fold(&self, fold: Fold) -> AocResult<()> {
+        let (x_dim, y_dim) = self.grid.dimensions();
+        let out_of_bounds = match fold {
+            Fold::Y(pivot) => pivot >= y_dim,
+            Fold::X(pivot) => pivot >= x_dim,
+        };
+        if out_of_bounds {
+            return Err(AocError::ChallengeError(format!(
+                "Can't fold along {:?} on paper of size {}x{}",
+                fold, x_dim, y_dim
+            )));
         }
+        Ok(())
     }
 
+    // the paper is left untouched if the fold fails
     pub fn fold(&mut self, fold: Fold) -> AocResult<()> {
-        match fold {
-            Fold::Y(pivot) => self.fold_y(pivot),
-            Fold::X(pivot) => self.fold_x(pivot),
+        *self = self.folded(fold)?;
+        Ok(())
+    }
+
+    // applies the folds in order, returns the dot count after each of them
+    pub fn fold_all(&mut self, folds: &[Fold]) -> AocResult<Vec<usize>> {
+        let mut counts = Vec::with_capacity(folds.len());
+        for &fold in folds {
+            self.fold(fold)?;
+            counts.push(self.count_dots());
         }
+        Ok(counts)
     }
 
-    fn fold_y(&mut self, pivot_y: usize) -> AocResult<()> {
-        for (offset, y) in (pivot_y..self.grid.row_count()).enumerate() {
-            for x in 0..self.grid.column_count() {
-                if let Some(Dot::Marked) = self.grid.get(x, y).map(|g| g.value) {
-                    if !self.mark(x, pivot_y - offset) {
-                        return Err(AocError::ChallengeError(format!(
-                            "Folding along y={} out of bounds on {},{}",
-                            pivot_y, x, y
-                        )));
+    // folds a copy of the paper, which only covers the part in front of the fold line
+    pub fn folded(&self, fold: Fold) -> AocResult<Paper> {
+        self.check_fold(fold)?;
+        let (x_dim, y_dim) = self.grid.dimensions();
+        let (pivot, folded_x_dim, folded_y_dim) = match fold {
+            Fold::Y(pivot) => (pivot, x_dim, pivot),
+            Fold::X(pivot) => (pivot, pivot, y_dim),
+        };
+        if pivot == 0 {
+            return Err(AocError::ChallengeError(format!(
+                "Folding along {:?} leaves no paper",
+                fold
+            )));
+        }
+        // dots on the fold line disappear, dots beyond it are reflected onto the part in front
+        // the paper may extend further than twice the pivot, as long as there are no dots
+        let reflect = |coordinate: usize| match coordinate.cmp(&pivot) {
+            Ordering::Less => Ok(Some(coordinate)),
+            Ordering::Equal => Ok(None),
+            Ordering::Greater => (2 * pivot)
+                .checked_sub(coordinate)
+                .map(Some)
+                .ok_or_else(|| {
+                    AocError::ChallengeError(format!(
+                        "Folding along {:?} out of bounds on {}",
+                        fold, coordinate
+                    ))
+                }),
+        };
+
+        let grid = Grid::with_default(folded_x_dim, folded_y_dim, Dot::Empty);
+        let mut paper = Paper { grid };
+        for y in 0..y_dim {
+            for x in 0..x_dim {
+                if let Some(Dot::Marked) = self.grid.get(x, y).map(|d| *d.value) {
+                    let folded = match fold {
+                        Fold::Y(_) => reflect(y)?.map(|y| (x, y)),
+                        Fold::X(_) => reflect(x)?.map(|x| (x, y)),
+                    };
+                    if let Some((x, y)) = folded {
+                        paper.mark(x, y);
                     }
-                    self.unmark(x, y);
                 }
             }
         }
-        Ok(())
+        Ok(paper)
     }
 
-    fn fold_x(&mut self, pivot_x: usize) -> AocResult<()> {
-        for y in 0..self.grid.row_count() {
-            for (offset, x) in (pivot_x..self.grid.column_count()).enumerate() {
-                if let Some(Dot::Marked) = self.grid.get(x, y).map(|g| g.value) {
-                    if !self.mark(pivot_x - offset, y) {
-                        return Err(AocError::ChallengeError(format!(
-                            "Folding along y={} out of bounds on {},{}",
-                            pivot_x, x, y
-                        )));
-                    }
-                    self.unmark(x, y);
+    // renders only the bounding box of all marked dots, empty if there are none
+    fn render_trimmed(&self) -> String {
+        let (x_dim, y_dim) = self.grid.dimensions();
+        let marked: Vec<_> = (0..y_dim)
+            .flat_map(|y| (0..x_dim).map(move |x| (x, y)))
+            .filter(|&(x, y)| matches!(self.grid.get(x, y).map(|d| *d.value), Some(Dot::Marked)))
+            .collect();
+        if marked.is_empty() {
+            return String::new();
+        }
+        let xs = marked.iter().map(|&(x, _)| x);
+        let ys = marked.iter().map(|&(_, y)| y);
+        let (x_min, x_max) = (xs.clone().min().unwrap_or(0), xs.max().unwrap_or(0));
+        let (y_min, y_max) = (ys.clone().min().unwrap_or(0), ys.max().unwrap_or(0));
+
+        (y_min..=y_max)
+            .map(|y| {
+                (x_min..=x_max)
+                    .map(|x| match self.grid.get(x, y).map(|d| *d.value) {
+                        Some(Dot::Marked) => '#',
+                        _ => ' ',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // reads the marked dots as capital letters, which have to fit into the top rows
+    // None if there are no dots, they don't fit or a glyph is unknown
+    fn decode_letters(&self) -> Option<String> {
+        let (x_dim, y_dim) = self.grid.dimensions();
+        let mut columns = vec![[false; LETTER_HEIGHT]; x_dim];
+        for y in 0..y_dim {
+            for (x, column) in columns.iter_mut().enumerate() {
+                if let Some(Dot::Marked) = self.grid.get(x, y).map(|d| *d.value) {
+                    *column.get_mut(y)? = true;
                 }
             }
         }
-        Ok(())
+        let glyphs = split_glyphs(&columns);
+        if glyphs.is_empty() {
+            return None;
+        }
+        glyphs.iter().map(|glyph| decode_glyph(glyph)).collect()
     }
 
     fn count_dots(&self) -> usize {
@@ -197,26 +315,14 @@ fn main() -> AocResult<()> {
             AocError::ChallengeError("Something went wrong during paper creation".to_string())
         })?;
 
-        let mut fold_iter = folds.iter();
-
-        if let Some(first_fold) = fold_iter.next() {
-            paper.fold(*first_fold)?;
-            println!("Total dot count after first fold: {}", paper.count_dots());
+        let dot_counts = paper.fold_all(&folds)?;
+        if let Some(first_count) = dot_counts.first() {
+            println!("Total dot count after first fold: {}", first_count);
         }
 
-        for f in fold_iter {
-            paper.fold(*f)?;
-        }
-
-        for y in 0..10 {
-            for dot in paper.grid.iter_row(y).take(50) {
-                let print_clearly = match dot.value {
-                    Dot::Marked => '#',
-                    Dot::Empty => ' ',
-                };
-                print!("{}", print_clearly);
-            }
-            println!();
+        println!("{}", paper.render_trimmed());
+        if let Some(letters) = paper.decode_letters() {
+            println!("Decoded letters: {}", letters);
         }
     }
 
@@ -230,20 +336,25 @@ mod tests {
 
     static TEST_INPUT: &str = "day13.testinput";
 
-    #[test]
-    fn example_part1() {
-        let input = helpers::read_file_string(TEST_INPUT).unwrap();
+    fn parse_input(input: &str) -> (Vec<Point>, Vec<Fold>) {
         let (points, folds) = input.split_once("\n\n").unwrap();
-        let points: Vec<Point> = points
+        let points = points
             .lines()
             .map(Point::from_str)
             .collect::<AocResult<_>>()
             .unwrap();
-        let folds: Vec<Fold> = folds
+        let folds = folds
             .lines()
             .map(Fold::from_str)
             .collect::<AocResult<_>>()
             .unwrap();
+        (points, folds)
+    }
+
+    #[test]
+    fn example_part1() {
+        let input = helpers::read_file_string(TEST_INPUT).unwrap();
+        let (points, folds) = parse_input(&input);
 
         let mut paper = Paper::with_points(&points).unwrap();
 
@@ -256,4 +367,155 @@ mod tests {
         assert_eq!(fold1_count, 17);
         assert_eq!(fold2_count, 16);
     }
+
+    #[test]
+    fn example_folded() {
+        let input = helpers::read_file_string(TEST_INPUT).unwrap();
+        let (points, folds) = parse_input(&input);
+        let paper = Paper::with_points(&points).unwrap();
+
+        let once = paper.folded(folds[0]).unwrap();
+        let twice = once.folded(folds[1]).unwrap();
+
+        assert_eq!(once.count_dots(), 17);
+        assert_eq!(once.grid.dimensions(), (11, 7));
+        assert_eq!(twice.count_dots(), 16);
+        assert_eq!(twice.grid.dimensions(), (5, 7));
+        // the original paper is left untouched
+        assert_eq!(paper.count_dots(), points.len());
+        assert!(paper.folded(Fold::X(0)).is_err());
+        assert!(paper.folded(Fold::Y(3)).is_err());
+        assert!(matches!(
+            paper.folded(Fold::X(11)),
+            Err(AocError::ChallengeError(message)) if message.contains("X(11)")
+        ));
+    }
+
+    #[test]
+    fn asymmetric_folds() {
+        let points = [
+            Point::new(0, 0),
+            Point::new(1, 1),
+            Point::new(2, 5),
+            Point::new(0, 4),
+            Point::new(3, 0),
+        ];
+        let mut paper = Paper::with_points(&points).unwrap();
+        let mut too_short = Paper::with_points(&points).unwrap();
+
+        // (2,5) is reflected onto (2,3), (0,4) lies on the fold line
+        paper.fold(Fold::Y(4)).unwrap();
+        let first_count = paper.count_dots();
+        // the paper is now 4 rows high
+        paper.fold(Fold::Y(2)).unwrap();
+        let second_count = paper.count_dots();
+        // (3,0) lies beyond twice the pivot
+        let folded_x = paper.fold(Fold::X(1));
+
+        assert_eq!(first_count, 4);
+        assert_eq!(second_count, 4);
+        assert!(matches!(
+            paper.grid.get(2, 1).map(|d| *d.value),
+            Some(Dot::Marked)
+        ));
+        assert!(matches!(folded_x, Err(AocError::ChallengeError(_))));
+        assert!(matches!(
+            too_short.fold(Fold::Y(2)),
+            Err(AocError::ChallengeError(_))
+        ));
+    }
+
+    #[test]
+    fn example_render_trimmed() {
+        let input = helpers::read_file_string(TEST_INPUT).unwrap();
+        let (points, folds) = parse_input(&input);
+        let mut paper = Paper::with_points(&points).unwrap();
+        for fold in folds {
+            paper.fold(fold).unwrap();
+        }
+
+        let rendered = paper.render_trimmed();
+        let rows: Vec<_> = rendered.lines().collect();
+
+        // The instructions made a square!
+        assert_eq!(rows, ["#####", "#   #", "#   #", "#   #", "#####"]);
+        assert!(rows.iter().all(|row| row.contains('#')));
+        assert!(rows.iter().any(|row| row.starts_with('#')));
+        assert!(rows.iter().any(|row| row.ends_with('#')));
+        assert_eq!(
+            Paper::with_points(&[Point::new(3, 2)])
+                .unwrap()
+                .render_trimmed(),
+            "#"
+        );
+    }
+
+    #[test]
+    fn example_fold_all() {
+        let input = helpers::read_file_string(TEST_INPUT).unwrap();
+        let (points, folds) = parse_input(&input);
+        let mut paper = Paper::with_points(&points).unwrap();
+
+        let counts = paper.fold_all(&folds).unwrap();
+
+        assert_eq!(counts, [17, 16]);
+        assert_eq!(paper.count_dots(), 16);
+        assert_eq!(paper.fold_all(&[]).unwrap(), []);
+    }
+
+    #[test]
+    fn fold_outside_of_paper() {
+        let points = [Point::new(0, 0), Point::new(4, 2)];
+        let mut paper = Paper::with_points(&points).unwrap();
+
+        let fold_x = paper.fold(Fold::X(5));
+        let fold_y = paper.fold(Fold::Y(7));
+
+        assert!(matches!(
+            fold_x,
+            Err(AocError::ChallengeError(message)) if message.contains("X(5)")
+        ));
+        assert!(matches!(
+            fold_y,
+            Err(AocError::ChallengeError(message)) if message.contains("Y(7)")
+        ));
+        assert_eq!(paper.count_dots(), 2);
+        assert!(paper.fold(Fold::X(2)).is_ok());
+    }
+
+    #[test]
+    fn failed_fold_leaves_paper_unchanged() {
+        let points = [Point::new(0, 0), Point::new(0, 3), Point::new(0, 6)];
+        let mut paper = Paper::with_points(&points).unwrap();
+
+        // (0,6) lies beyond twice the pivot, after (0,3) would have been reflected
+        let folded = paper.fold(Fold::Y(2));
+        let marked =
+            |paper: &Paper, y| matches!(paper.grid.get(0, y).map(|d| *d.value), Some(Dot::Marked));
+
+        assert!(matches!(folded, Err(AocError::ChallengeError(_))));
+        assert_eq!(paper.grid.dimensions(), (1, 7));
+        assert!(marked(&paper, 3));
+        assert!(!marked(&paper, 1));
+        // later folds are checked against the folded size
+        paper.fold(Fold::Y(3)).unwrap();
+        assert_eq!(paper.grid.dimensions(), (1, 3));
+        assert!(paper.fold(Fold::Y(3)).is_err());
+    }
+
+    #[test]
+    fn decode_folded_letters() {
+        let input = helpers::read_file_string("day13.letters.testinput").unwrap();
+        let (points, folds) = parse_input(&input);
+
+        let mut paper = Paper::with_points(&points).unwrap();
+        let unfolded = paper.decode_letters();
+        paper.fold(folds[0]).unwrap();
+
+        assert_eq!(unfolded, None);
+        assert_eq!(paper.decode_letters(), Some("HI".to_string()));
+        // two neighbouring dots are no letter
+        let unknown = Paper::with_points(&[Point::new(0, 0), Point::new(1, 0)]).unwrap();
+        assert_eq!(unknown.decode_letters(), None);
+    }
 }
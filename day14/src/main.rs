@@ -11,39 +11,19 @@ fn main() -> AocResult<()> {
         //Apply 10 steps of pair insertion to the polymer template and find the most and least
         // common elements in the result. What do you get if you take the quantity of the most
         // common element and subtract the quantity of the least common element?
-        let inserter = NaivePairInserter::new(rules.clone());
-        let mut next = template.to_string();
-        for _ in 0..10 {
-            next = inserter.pair_insert(&next)?;
-        }
-        if let MinMaxResult::MinMax((_, min), (_, max)) = NaivePairInserter::count_elements(&next)
-            .iter()
-            .minmax_by_key(|c| c.1)
-        {
-            println!("Max minus min: {}", max - min);
-        } else {
-            println!("Not enough elements");
+        let mut inserter = NaivePairInserter::new(rules.clone()).with_template(template);
+        match mce_answer(&inserter.polymerize_counts(10)?) {
+            Some(difference) => println!("Max minus min: {}", difference),
+            None => println!("Not enough elements"),
         }
 
         // Part 2
         // Apply 40 steps of pair insertion to the polymer template and find the most and least
         // common elements in the result. What do you get if you take the quantity of the most
         // common element and subtract the quantity of the least common element?
-        if let Some(mut stateful_inserter) = StatefulPairInserter::new(rules, template) {
-            for _ in 0..40 {
-                // let start = Instant::now();
-                stateful_inserter.step()?;
-                // println!("i: {} took {:?}", i, start.elapsed());
-            }
-            if let MinMaxResult::MinMax((_, min), (_, max)) = stateful_inserter
-                .count_elements()
-                .iter()
-                .minmax_by_key(|c| c.1)
-            {
-                println!("Max minus min: {}", max - min);
-            } else {
-                println!("Not enough elements");
-            }
+        match mce_answer(&polymerize(template, &rules, 40)?) {
+            Some(difference) => println!("Max minus min: {}", difference),
+            None => println!("Not enough elements"),
         }
     }
 
@@ -80,12 +60,52 @@ fn parse_rules(input: &str) -> AocResult<InsertionRules> {
     Ok(rules)
 }
 
+// quantity of the most common element minus the quantity of the least common element
+// None if there are fewer than two elements
+fn mce_answer(counts: &HashMap<char, usize>) -> Option<usize> {
+    match counts.values().minmax() {
+        MinMaxResult::MinMax(min, max) => Some(max - min),
+        _ => None,
+    }
+}
+
+// element counts of the polymer after applying the rules steps times to template
+// an empty template has no elements
+fn polymerize(
+    template: &str,
+    rules: &InsertionRules,
+    steps: usize,
+) -> AocResult<HashMap<char, usize>> {
+    match StatefulPairInserter::new(rules.clone(), template) {
+        Some(mut inserter) => inserter.polymerize_counts(steps),
+        None => Ok(HashMap::new()),
+    }
+}
+
+// applies steps more rounds of pair insertion and returns the element counts of the polymer
+trait PairInserter {
+    fn polymerize_counts(&mut self, steps: usize) -> AocResult<HashMap<char, usize>>;
+}
+
+// the polymer doubles in length with each step
+const NAIVE_MAX_STEPS: usize = 20;
+
 struct NaivePairInserter {
     rules: InsertionRules,
+    polymer: String,
 }
 impl NaivePairInserter {
     fn new(rules: InsertionRules) -> Self {
-        Self { rules }
+        Self {
+            rules,
+            polymer: String::new(),
+        }
+    }
+    fn with_template(self, template: &str) -> Self {
+        Self {
+            polymer: template.to_string(),
+            ..self
+        }
     }
     fn count_elements(s: &str) -> HashMap<char, usize> {
         let mut result = HashMap::new();
@@ -121,6 +141,21 @@ impl NaivePairInserter {
     }
 }
 
+impl PairInserter for NaivePairInserter {
+    fn polymerize_counts(&mut self, steps: usize) -> AocResult<HashMap<char, usize>> {
+        if steps > NAIVE_MAX_STEPS {
+            return Err(AocError::ChallengeError(format!(
+                "Naive pair insertion is limited to {} steps at once, got {}",
+                NAIVE_MAX_STEPS, steps
+            )));
+        }
+        for _ in 0..steps {
+            self.polymer = self.pair_insert(&self.polymer)?;
+        }
+        Ok(Self::count_elements(&self.polymer))
+    }
+}
+
 struct StatefulPairInserter {
     rules: InsertionRules,
     state: HashMap<(char, char), usize>,
@@ -159,7 +194,9 @@ impl StatefulPairInserter {
         let old = self.state.clone();
         let mut new = HashMap::new();
         for (current, count) in old.iter() {
-            let center = *self.rules.get(current).unwrap();
+            let center = *self.rules.get(current).ok_or_else(|| {
+                AocError::ChallengeError(format!("No rule found for tuple {:?}", current))
+            })?;
             // add two new tuples
             let left = (current.0, center);
             let right = (center, current.1);
@@ -177,6 +214,15 @@ impl StatefulPairInserter {
     }
 }
 
+impl PairInserter for StatefulPairInserter {
+    fn polymerize_counts(&mut self, steps: usize) -> AocResult<HashMap<char, usize>> {
+        for _ in 0..steps {
+            self.step()?;
+        }
+        Ok(self.count_elements())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +265,72 @@ mod tests {
         assert_eq!(most_common.1 - least_common.1, 1588);
     }
 
+    #[test]
+    fn example_polymerize() {
+        let rules = parse_rules(TEST_RULES).unwrap();
+
+        let counts = polymerize("NNCB", &rules, 10).unwrap();
+        let unchanged = polymerize("NNCB", &rules, 0).unwrap();
+        let empty = polymerize("", &rules, 10).unwrap();
+
+        assert_eq!(counts.get(&'B'), Some(&1749));
+        assert_eq!(counts.get(&'C'), Some(&298));
+        assert_eq!(counts.get(&'H'), Some(&161));
+        assert_eq!(counts.get(&'N'), Some(&865));
+        assert_eq!(counts.len(), 4);
+        assert_eq!(unchanged.get(&'N'), Some(&2));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn inserters_agree() {
+        let rules = parse_rules(TEST_RULES).unwrap();
+        let mut inserters: Vec<Box<dyn PairInserter>> = vec![
+            Box::new(NaivePairInserter::new(rules.clone()).with_template("NNCB")),
+            Box::new(StatefulPairInserter::new(rules.clone(), "NNCB").unwrap()),
+        ];
+
+        let counts: Vec<_> = inserters
+            .iter_mut()
+            .map(|inserter| inserter.polymerize_counts(10).unwrap())
+            .collect();
+        let too_many_steps = NaivePairInserter::new(rules)
+            .with_template("NNCB")
+            .polymerize_counts(NAIVE_MAX_STEPS + 1);
+
+        assert_eq!(counts[0], counts[1]);
+        assert_eq!(counts[0].get(&'B'), Some(&1749));
+        assert!(too_many_steps.is_err());
+    }
+
+    #[test]
+    fn missing_rule() {
+        let rules = parse_rules("NN -> C").unwrap();
+        let mut naive = NaivePairInserter::new(rules.clone()).with_template("NB");
+
+        assert!(matches!(
+            polymerize("NB", &rules, 1),
+            Err(AocError::ChallengeError(_))
+        ));
+        assert!(matches!(
+            naive.polymerize_counts(1),
+            Err(AocError::ChallengeError(_))
+        ));
+        // no step, no rule needed
+        assert!(polymerize("NB", &rules, 0).is_ok());
+    }
+
+    #[test]
+    fn example_mce_answer() {
+        let rules = parse_rules(TEST_RULES).unwrap();
+        let counts = polymerize("NNCB", &rules, 10).unwrap();
+
+        assert_eq!(mce_answer(&counts), Some(1588));
+        assert_eq!(mce_answer(&HashMap::from([('N', 3)])), None);
+        assert_eq!(mce_answer(&HashMap::new()), None);
+        assert_eq!(mce_answer(&HashMap::from([('N', 3), ('B', 3)])), Some(0));
+    }
+
     #[test]
     fn example_part2() {
         let template = "NNCB";
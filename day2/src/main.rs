@@ -1,4 +1,5 @@
-use helpers::{read_lines_parse, AocError, AocResult};
+use helpers::{AocError, AocResult, Solution};
+use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
 type Unit = i32;
@@ -8,6 +9,8 @@ enum Direction {
     Up(Unit),
     Down(Unit),
     Forward(Unit),
+    // moves sideways, only understood by submarines navigating in three dimensions
+    Strafe(Unit),
 }
 
 impl FromStr for Direction {
@@ -20,6 +23,7 @@ impl FromStr for Direction {
                 "forward" => Ok(Direction::Forward(unit)),
                 "up" => Ok(Direction::Up(unit)),
                 "down" => Ok(Direction::Down(unit)),
+                "strafe" => Ok(Direction::Strafe(unit)),
                 _ => Err(AocError::ParseStructError(format!(
                     "Unknown Direction {}",
                     direction
@@ -34,25 +38,90 @@ impl FromStr for Direction {
     }
 }
 
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Direction::Up(n) => write!(f, "up {}", n),
+            Direction::Down(n) => write!(f, "down {}", n),
+            Direction::Forward(n) => write!(f, "forward {}", n),
+            Direction::Strafe(n) => write!(f, "strafe {}", n),
+        }
+    }
+}
+
+impl Direction {
+    // the direction that reverses this one
+    #[cfg(test)]
+    fn inverse(self) -> Direction {
+        match self {
+            Direction::Up(n) => Direction::Down(n),
+            Direction::Down(n) => Direction::Up(n),
+            Direction::Forward(n) => Direction::Forward(-n),
+            Direction::Strafe(n) => Direction::Strafe(-n),
+        }
+    }
+
+    // parses one direction per line, errors name the offending line
+    fn parse_all(s: &str) -> AocResult<Vec<Direction>> {
+        s.lines()
+            .enumerate()
+            .map(|(n, line)| {
+                line.parse().map_err(|e| {
+                    AocError::ParseStructError(format!("Line {} '{}': {}", n + 1, line, e))
+                })
+            })
+            .collect()
+    }
+}
+
+// a navigation model interpreting directions
+trait Navigable {
+    fn go(&mut self, direction: Direction);
+
+    // position multiplied by depth
+    fn answer(&self) -> Unit;
+
+    fn go_n(&mut self, directions: &[Direction]) {
+        for direction in directions {
+            self.go(*direction)
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct Submarine {
     depth: Unit,
     position: Unit,
+    history: Vec<Direction>,
 }
 
 impl Submarine {
-    fn go(&mut self, direction: Direction) {
+    fn apply(&mut self, direction: Direction) {
         match direction {
             Direction::Up(n) => self.depth -= n,
             Direction::Down(n) => self.depth += n,
             Direction::Forward(n) => self.position += n,
+            Direction::Strafe(_) => {}
         }
     }
 
-    fn go_n(&mut self, directions: &[Direction]) {
-        for direction in directions {
-            self.go(*direction)
-        }
+    // reverses the last move and returns it, None if there is nothing left to undo
+    #[cfg(test)]
+    fn undo(&mut self) -> Option<Direction> {
+        let last = self.history.pop()?;
+        self.apply(last.inverse());
+        Some(last)
+    }
+}
+
+impl Navigable for Submarine {
+    fn go(&mut self, direction: Direction) {
+        self.apply(direction);
+        self.history.push(direction);
+    }
+
+    fn answer(&self) -> Unit {
+        self.position * self.depth
     }
 }
 
@@ -61,10 +130,11 @@ struct Aimmarine {
     aim: Unit,
     depth: Unit,
     position: Unit,
+    history: Vec<Direction>,
 }
 
 impl Aimmarine {
-    fn go(&mut self, direction: Direction) {
+    fn apply(&mut self, direction: Direction) {
         match direction {
             // Again note that since you're on a submarine, down and up do the opposite of what
             // you might expect: "down" means aiming in the positive direction.
@@ -74,38 +144,96 @@ impl Aimmarine {
                 self.position += n;
                 self.depth += self.aim * n;
             }
+            Direction::Strafe(_) => {}
         }
     }
 
-    fn go_n(&mut self, directions: &[Direction]) {
-        for direction in directions {
-            self.go(*direction)
+    // reverses the last move and returns it, None if there is nothing left to undo
+    #[cfg(test)]
+    fn undo(&mut self) -> Option<Direction> {
+        let last = self.history.pop()?;
+        self.apply(last.inverse());
+        Some(last)
+    }
+}
+
+impl Navigable for Aimmarine {
+    fn go(&mut self, direction: Direction) {
+        self.apply(direction);
+        self.history.push(direction);
+    }
+
+    fn answer(&self) -> Unit {
+        self.position * self.depth
+    }
+}
+
+// moves like the Submarine, with an additional lateral axis y for strafing
+#[derive(Debug, Default)]
+struct Submarine3D {
+    x: Unit,
+    y: Unit,
+    depth: Unit,
+}
+
+impl Navigable for Submarine3D {
+    fn go(&mut self, direction: Direction) {
+        match direction {
+            Direction::Up(n) => self.depth -= n,
+            Direction::Down(n) => self.depth += n,
+            Direction::Forward(n) => self.x += n,
+            Direction::Strafe(n) => self.y += n,
         }
     }
+
+    fn answer(&self) -> Unit {
+        self.x * self.depth
+    }
 }
 
-fn main() -> AocResult<()> {
-    let input: Vec<Direction> = read_lines_parse("day2/day2.input")?;
+struct Day2;
+
+impl Solution for Day2 {
+    type Input = Vec<Direction>;
+
+    fn parse(s: &str) -> AocResult<Self::Input> {
+        Direction::parse_all(s)
+    }
+
+    fn part1(input: &Self::Input) -> String {
+        let mut sub = Submarine::default();
+        sub.go_n(input);
+        format!(
+            "Position: {}, Depth: {}, Product = {}",
+            sub.position,
+            sub.depth,
+            sub.answer()
+        )
+    }
 
-    let mut sub = Submarine::default();
-    sub.go_n(&input);
-    println!(
-        "Position: {}, Depth: {}, Product = {}",
-        sub.position,
-        sub.depth,
-        sub.position * sub.depth
-    );
+    fn part2(input: &Self::Input) -> String {
+        let mut aim = Aimmarine::default();
+        aim.go_n(input);
+        format!(
+            "Position: {}, Depth: {}, Product = {}",
+            aim.position,
+            aim.depth,
+            aim.answer()
+        )
+    }
 
-    let mut aim = Aimmarine::default();
-    aim.go_n(&input);
-    println!(
-        "Position: {}, Depth: {}, Product = {}",
-        aim.position,
-        aim.depth,
-        aim.position * aim.depth
-    );
+    fn details(input: &Self::Input) -> Vec<String> {
+        let mut sub_3d = Submarine3D::default();
+        sub_3d.go_n(input);
+        vec![format!(
+            "3D Position: {}, Lateral: {}, Depth: {}",
+            sub_3d.x, sub_3d.y, sub_3d.depth
+        )]
+    }
+}
 
-    Ok(())
+fn main() -> AocResult<()> {
+    Day2::run("day2/day2.input")
 }
 
 #[cfg(test)]
@@ -135,6 +263,105 @@ forward 2";
         assert_eq!(correctly_parsed, DIRECTIONS)
     }
 
+    #[test]
+    fn example_solution() {
+        let input = Day2::parse("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2").unwrap();
+
+        assert_eq!(
+            Day2::part1(&input),
+            "Position: 15, Depth: 10, Product = 150"
+        );
+        assert_eq!(
+            Day2::part2(&input),
+            "Position: 15, Depth: 60, Product = 900"
+        );
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let formatted: Vec<_> = DIRECTIONS.iter().map(|d| d.to_string()).collect();
+
+        assert_eq!(formatted[..2], ["forward 5", "down 5"]);
+        for direction in DIRECTIONS
+            .iter()
+            .chain(&[Direction::Up(0), Direction::Strafe(-4)])
+        {
+            let parsed = Direction::from_str(&direction.to_string()).unwrap();
+            assert_eq!(parsed, *direction);
+        }
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let valid = Direction::parse_all("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2");
+        let invalid = Direction::parse_all("forward 5\nsideways 5\nup 3");
+
+        assert_eq!(valid.unwrap(), DIRECTIONS);
+        let message = invalid.unwrap_err().to_string();
+        assert!(message.contains("Line 2"));
+        assert!(message.contains("sideways"));
+    }
+
+    #[test]
+    fn strafe_in_three_dimensions() {
+        let strafe: Direction = "strafe 4".parse().unwrap();
+        let unknown = "sideways 4".parse::<Direction>();
+        let mut sub = Submarine3D::default();
+
+        sub.go_n(&[
+            Direction::Forward(5),
+            strafe,
+            Direction::Down(3),
+            Direction::Strafe(-1),
+            Direction::Up(1),
+        ]);
+
+        assert_eq!(strafe, Direction::Strafe(4));
+        assert!(unknown.is_err());
+        assert_eq!((sub.x, sub.y, sub.depth), (5, 3, 2));
+    }
+
+    #[test]
+    fn undo_moves() {
+        let mut sub = Submarine::default();
+        let mut aim = Aimmarine::default();
+
+        sub.go_n(&[Direction::Forward(5), Direction::Down(3)]);
+        aim.go_n(&[Direction::Down(3), Direction::Forward(5)]);
+        let aim_depth = aim.depth;
+        let sub_undone = [sub.undo(), sub.undo(), sub.undo()];
+        let aim_undone = [aim.undo(), aim.undo(), aim.undo()];
+
+        assert_eq!(
+            sub_undone,
+            [Some(Direction::Down(3)), Some(Direction::Forward(5)), None]
+        );
+        assert_eq!((sub.position, sub.depth), (0, 0));
+        assert_eq!(aim_depth, 15);
+        assert_eq!(
+            aim_undone,
+            [Some(Direction::Forward(5)), Some(Direction::Down(3)), None]
+        );
+        assert_eq!((aim.position, aim.depth, aim.aim), (0, 0, 0));
+    }
+
+    #[test]
+    fn navigate_dynamically() {
+        let mut sub = Submarine::default();
+        let mut aim = Aimmarine::default();
+        let models: [&mut dyn Navigable; 2] = [&mut sub, &mut aim];
+
+        let answers: Vec<_> = models
+            .into_iter()
+            .map(|model| {
+                model.go_n(&DIRECTIONS);
+                model.answer()
+            })
+            .collect();
+
+        assert_eq!(answers, [150, 900]);
+    }
+
     #[test]
     fn example_part1() {
         let mut sub = Submarine::default();
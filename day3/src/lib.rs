@@ -0,0 +1,493 @@
+use helpers::{AocError, AocResult};
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+// The diagnostic report (your puzzle input) consists of a list of binary numbers which,
+// when decoded properly, can tell you many useful things about the conditions of the submarine.
+// The first parameter to check is the power consumption.
+//
+// You need to use the binary numbers in the diagnostic report to generate two new binary numbers
+// (called the gamma rate and the epsilon rate). The power consumption can then be found by
+// multiplying the gamma rate by the epsilon rate.
+
+// What to do if ones and zeroes are equally common in a position
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TieBreak {
+    PreferZero,
+    PreferOne,
+    Error,
+}
+
+impl TieBreak {
+    // picks the bit for a position, most_common is None if there is a tie
+    fn resolve(self, most_common: Option<bool>, position: usize) -> AocResult<bool> {
+        match (most_common, self) {
+            (Some(bit), _) => Ok(bit),
+            (None, TieBreak::PreferZero) => Ok(false),
+            (None, TieBreak::PreferOne) => Ok(true),
+            (None, TieBreak::Error) => Err(AocError::ChallengeError(format!(
+                "Ones and zeroes are equally common in position {}",
+                position
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report<'a> {
+    size: usize,
+    data: &'a [ReportBits],
+}
+
+impl<'a> Report<'a> {
+    pub fn from_bits(data: &'a [ReportBits]) -> Result<Self, AocError> {
+        if let Some(first) = data.first() {
+            let size = first.len();
+            if data.iter().any(|b| b.len() != size) {
+                return Err(AocError::ParseStructError(
+                    "uneven size/length of bits".to_string(),
+                ));
+            }
+            Ok(Report { size, data })
+        } else {
+            Err(AocError::ParseStructError(
+                "Reports may not be empty".to_string(),
+            ))
+        }
+    }
+
+    // Rows shorter than the widest one are left padded with zeroes, which keeps their value
+    pub fn from_bits_padded(data: &'a mut [ReportBits]) -> Result<Self, AocError> {
+        let width = data.iter().map(|b| b.len()).max().unwrap_or(0);
+        for bits in data.iter_mut() {
+            bits.width = width;
+        }
+        Self::from_bits(data)
+    }
+
+    // (ones, zeroes) for each position, starting from the left
+    pub fn bit_frequencies(&self) -> Vec<(usize, usize)> {
+        (0..self.size)
+            .map(|position| {
+                let ones = self
+                    .data
+                    .iter()
+                    .filter(|b| b.get(position) == Some(true))
+                    .count();
+                (ones, self.data.len() - ones)
+            })
+            .collect()
+    }
+
+    // Each bit in the gamma rate can be determined by finding the most common bit in the
+    // corresponding position of all numbers in the diagnostic report.
+    // It is not specified what should happen if they are equally common, tie_break decides.
+    pub fn gamma_rate(&self, tie_break: TieBreak) -> AocResult<ReportBits> {
+        let mut result_bits = ReportBits::zeroes(self.size);
+        for position in 0..self.size {
+            if tie_break.resolve(most_common_bit(self.data, position), position)? {
+                result_bits.set(position)
+            }
+        }
+
+        Ok(result_bits)
+    }
+
+    // The epsilon rate is calculated in a similar way [to the gamma rate];
+    // rather than use the most common bit, the least common bit from each position is used.
+    pub fn epsilon_rate(&self, tie_break: TieBreak) -> AocResult<ReportBits> {
+        let mut gamma_rate = self.gamma_rate(tie_break)?;
+        gamma_rate.negate();
+
+        Ok(gamma_rate)
+    }
+
+    // The power consumption can then be found by multiplying the gamma rate by the epsilon rate.
+    pub fn power_consumption(&self) -> u64 {
+        let gamma_rate = self
+            .gamma_rate(TieBreak::PreferZero)
+            .expect("Preferring zeroes always resolves ties");
+        let epsilon_rate = self
+            .epsilon_rate(TieBreak::PreferZero)
+            .expect("Preferring zeroes always resolves ties");
+        gamma_rate.to_decimal() * epsilon_rate.to_decimal()
+    }
+
+    // Finally, to find the life support rating, multiply the oxygen generator rating by the
+    // CO2 scrubber rating.
+    pub fn life_support_rating(&self) -> Option<u64> {
+        let oxygen_generator_rating = self.oxygen_generator_rating()?;
+        let co2_scrubber_rating = self.co2_scrubber_rating()?;
+        Some(oxygen_generator_rating.to_decimal() * co2_scrubber_rating.to_decimal())
+    }
+
+    // PART 2
+    // Before searching for either rating value, start with the full list of binary numbers from
+    // your diagnostic report and consider just the first bit of those numbers. Then:
+    //
+    //     Keep only numbers selected by the bit criteria for the type of rating value
+    //         for which you are searching. Discard numbers which do not match the bit criteria.
+    //     If you only have one number left, stop; this is the rating value for which you are
+    //         searching.
+    //     Otherwise, repeat the process, considering the next bit to the right.
+
+    pub fn oxygen_generator_rating(&self) -> Option<&'a ReportBits> {
+        self.reduce_to_single_rating(TieBreak::PreferOne, false)
+            .ok()
+            .flatten()
+    }
+
+    // returns Some(ReportBits) if a single ReportBit was left over, otherwise None.
+    // Errors if there is a tie while tie_break is TieBreak::Error
+    // Only the indexes of the remaining ReportBits are tracked, the data is never copied
+    // TODO get rid of third parameter
+    fn reduce_to_single_rating(
+        &self,
+        tie_break: TieBreak,
+        invert_common_bit: bool,
+    ) -> AocResult<Option<&'a ReportBits>> {
+        let mut remaining: Vec<usize> = (0..self.data.len()).collect();
+        for idx in 0..self.size {
+            if remaining.len() < 2 {
+                break;
+            }
+            // To find oxygen generator rating, determine the most common value (0 or 1)
+            // in the current bit position
+            // keep only numbers with that bit in that position.
+            // If 0 and 1 are equally common, keep values selected by tie_break in the position being considered.
+            let common_bit = most_common_bit(remaining.iter().map(|&i| &self.data[i]), idx)
+                .map(|cb| if invert_common_bit { !cb } else { cb });
+            let common_bit = tie_break.resolve(common_bit, idx)?;
+
+            remaining.retain(|&i| self.data[i].get(idx) == Some(common_bit));
+        }
+
+        match remaining[..] {
+            [single] => Ok(Some(&self.data[single])),
+            _ => Ok(None),
+        }
+    }
+
+    // To find CO2 scrubber rating, determine the least common value (0 or 1) in the current bit
+    // position, and keep only numbers with that bit in that position.
+    // If 0 and 1 are equally common, keep values with a 0 in the position being considered.
+    pub fn co2_scrubber_rating(&self) -> Option<&'a ReportBits> {
+        self.reduce_to_single_rating(TieBreak::PreferZero, true)
+            .ok()
+            .flatten()
+    }
+}
+
+// Bits are indexed from the left, such that index 0 is the most significant of width bits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReportBits {
+    bits: u64,
+    width: usize,
+}
+
+const MAX_REPORT_WIDTH: usize = u64::BITS as usize;
+
+impl ReportBits {
+    fn zeroes(width: usize) -> Self {
+        ReportBits { bits: 0, width }
+    }
+    fn len(&self) -> usize {
+        self.width
+    }
+    fn get(&self, idx: usize) -> Option<bool> {
+        if idx < self.width {
+            Some(self.bits & self.position_mask(idx) != 0)
+        } else {
+            None
+        }
+    }
+    fn set(&mut self, idx: usize) {
+        self.bits |= self.position_mask(idx)
+    }
+    // hex digits are read as 4 bits each, only the lowest width bits are kept
+    pub fn from_hex(s: &str, width: usize) -> AocResult<ReportBits> {
+        if width > MAX_REPORT_WIDTH {
+            return Err(AocError::ParseStructError(format!(
+                "Reports may be at most {} bits wide, got {}",
+                MAX_REPORT_WIDTH, width
+            )));
+        }
+
+        let mut bits = 0;
+        for c in s.chars() {
+            let digit = c.to_digit(16).ok_or_else(|| {
+                AocError::ParseStructError(format!("'{}' is not a valid hex digit", c))
+            })?;
+            bits = (bits << 4) | u64::from(digit);
+        }
+
+        let mut report_bits = ReportBits { bits, width };
+        report_bits.truncate();
+        Ok(report_bits)
+    }
+    fn negate(&mut self) {
+        self.bits = !self.bits;
+        self.truncate()
+    }
+    // only the lowest width bits are part of the report
+    fn truncate(&mut self) {
+        let width_mask = u64::MAX
+            .checked_shr((MAX_REPORT_WIDTH - self.width) as u32)
+            .unwrap_or(0);
+        self.bits &= width_mask
+    }
+    fn position_mask(&self, idx: usize) -> u64 {
+        1 << (self.width - 1 - idx)
+    }
+    pub fn to_decimal(&self) -> u64 {
+        self.bits
+    }
+}
+
+// returns Some(true|false) if none is prevalent, None is returned.
+fn most_common_bit<'a>(bits: impl IntoIterator<Item = &'a ReportBits>, idx: usize) -> Option<bool> {
+    let (mut ones, mut zeroes) = (0, 0);
+    for b in bits {
+        if let Some(true) = b.get(idx) {
+            ones += 1
+        } else {
+            zeroes += 1
+        }
+    }
+
+    match ones.cmp(&zeroes) {
+        Ordering::Less => Some(false),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(true),
+    }
+}
+
+// this ignore all chars that are neither 0 nor 1
+impl FromStr for ReportBits {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let width = s.chars().count();
+        if width > MAX_REPORT_WIDTH {
+            return Err(AocError::ParseStructError(format!(
+                "Reports may be at most {} bits wide, got {}",
+                MAX_REPORT_WIDTH, width
+            )));
+        }
+
+        let mut bits = ReportBits::zeroes(width);
+        for (n, c) in s.chars().enumerate() {
+            match c {
+                // all bits are already 0
+                '0' => {}
+                // set position n to 1
+                '1' => bits.set(n),
+                _ => {
+                    return Err(AocError::ParseStructError(format!(
+                        "'{}' is not a valid bit",
+                        c
+                    )))
+                }
+            }
+        }
+
+        Ok(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helpers::{read_file_string, read_lines_parse};
+
+    #[test]
+    fn parse_report_bits() {
+        let lines = read_file_string("day3.testinput").unwrap();
+
+        for line in lines.lines() {
+            let bits = ReportBits::from_str(line).unwrap();
+            let expected_bits: Vec<_> = line.chars().map(|c| c == '1').collect();
+            let bits_from_left: Vec<_> = (0..bits.len()).map(|i| bits.get(i).unwrap()).collect();
+
+            assert_eq!(bits.to_decimal(), u64::from_str_radix(line, 2).unwrap());
+            assert_eq!(bits_from_left, expected_bits);
+            assert_eq!(bits.get(bits.len()), None);
+        }
+
+        let widest = ReportBits::from_str(&"1".repeat(64)).unwrap();
+        let mut negated = widest.clone();
+        negated.negate();
+        assert_eq!(widest.to_decimal(), u64::MAX);
+        assert_eq!(negated.to_decimal(), 0);
+        assert!(ReportBits::from_str(&"1".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn parse_hex() {
+        let hex = ReportBits::from_hex("16", 5).unwrap();
+        let padded = ReportBits::from_hex("16", 8).unwrap();
+        let truncated = ReportBits::from_hex("f6", 5).unwrap();
+        let invalid = ReportBits::from_hex("1g", 5);
+        let too_wide = ReportBits::from_hex("1", 65);
+
+        assert_eq!(hex, ReportBits::from_str("10110").unwrap());
+        assert_eq!(padded, ReportBits::from_str("00010110").unwrap());
+        assert_eq!(truncated, ReportBits::from_str("10110").unwrap());
+        assert!(invalid.is_err());
+        assert!(too_wide.is_err());
+    }
+
+    #[test]
+    fn example_part1() {
+        let input: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+        let report = Report::from_bits(&input).unwrap();
+
+        let gamma_rate = report.gamma_rate(TieBreak::Error).unwrap();
+        let gamma_rate_decimal = gamma_rate.to_decimal();
+
+        let epsilon_rate = report.epsilon_rate(TieBreak::Error).unwrap();
+        let epsilon_rate_decimal = epsilon_rate.to_decimal();
+
+        // So, the gamma rate is the binary number 10110, or 22 in decimal.
+        assert_eq!(gamma_rate, ReportBits::from_str("10110").unwrap());
+        assert_eq!(gamma_rate_decimal, 22);
+
+        //So, the epsilon rate is 01001, or 9 in decimal.
+        assert_eq!(epsilon_rate, ReportBits::from_str("01001").unwrap());
+        assert_eq!(epsilon_rate_decimal, 9)
+    }
+
+    #[test]
+    fn variable_width_rows() {
+        let mut input = [
+            ReportBits::from_str("101").unwrap(),
+            ReportBits::from_str("00011").unwrap(),
+            ReportBits::from_str("111").unwrap(),
+        ];
+        let mut uniform: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+
+        let uneven = Report::from_bits(&input).is_err();
+        let report = Report::from_bits_padded(&mut input).unwrap();
+        let gamma_rate = report.gamma_rate(TieBreak::Error).unwrap();
+        let epsilon_rate = report.epsilon_rate(TieBreak::Error).unwrap();
+        let uniform_report = Report::from_bits_padded(&mut uniform).unwrap();
+
+        assert!(uneven);
+        assert_eq!(gamma_rate, ReportBits::from_str("00111").unwrap());
+        assert_eq!(epsilon_rate, ReportBits::from_str("11000").unwrap());
+        assert_eq!(input[0].to_decimal(), 5);
+        assert_eq!(
+            uniform_report
+                .gamma_rate(TieBreak::Error)
+                .unwrap()
+                .to_decimal(),
+            22
+        );
+    }
+
+    #[test]
+    fn tie_break_policies() {
+        let input = [
+            ReportBits::from_str("10").unwrap(),
+            ReportBits::from_str("01").unwrap(),
+        ];
+        let report = Report::from_bits(&input).unwrap();
+
+        let gamma_zero = report.gamma_rate(TieBreak::PreferZero).unwrap();
+        let gamma_one = report.gamma_rate(TieBreak::PreferOne).unwrap();
+        let gamma_error = report.gamma_rate(TieBreak::Error);
+        let rating_zero = report.reduce_to_single_rating(TieBreak::PreferZero, false);
+        let rating_one = report.reduce_to_single_rating(TieBreak::PreferOne, false);
+        let rating_error = report.reduce_to_single_rating(TieBreak::Error, false);
+
+        assert_eq!(gamma_zero.to_decimal(), 0b00);
+        assert_eq!(gamma_one.to_decimal(), 0b11);
+        assert!(gamma_error.is_err());
+        assert_eq!(rating_zero.unwrap(), Some(&input[1]));
+        assert_eq!(rating_one.unwrap(), Some(&input[0]));
+        assert!(rating_error.is_err());
+    }
+
+    #[test]
+    fn example_bit_frequencies() {
+        let input: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+        let report = Report::from_bits(&input).unwrap();
+
+        let frequencies = report.bit_frequencies();
+
+        assert_eq!(frequencies.len(), 5);
+        assert_eq!(frequencies.first(), Some(&(7, 5)));
+        assert_eq!(frequencies.last(), Some(&(5, 7)));
+    }
+
+    #[test]
+    fn example_power_and_life_support() {
+        let input: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+        let report = Report::from_bits(&input).unwrap();
+
+        assert_eq!(report.power_consumption(), 198);
+        assert_eq!(report.life_support_rating(), Some(230));
+    }
+
+    #[test]
+    fn example_ratings() {
+        let input: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+        let report = Report::from_bits(&input).unwrap();
+
+        let oxygen_generator_rating = report.oxygen_generator_rating().map(|r| r.to_decimal());
+        let co2_scrubber_rating = report.co2_scrubber_rating().map(|r| r.to_decimal());
+
+        assert_eq!(oxygen_generator_rating, Some(23));
+        assert_eq!(co2_scrubber_rating, Some(10));
+    }
+
+    #[test]
+    fn example_part2() {
+        let input: Vec<ReportBits> = read_lines_parse("day3.testinput").unwrap();
+        let report = Report::from_bits(&input).unwrap();
+
+        // For example, to determine the oxygen generator rating value using the same example
+        // diagnostic report from above:
+        //
+        //     Start with all 12 numbers and consider only the first bit of each number. There are
+        //         more 1 bits (7) than 0 bits (5), so keep only the 7 numbers with a 1 in the first
+        //         position: 11110, 10110, 10111, 10101, 11100, 10000, and 11001.
+        //     Then, consider the second bit of the 7 remaining numbers: there are more 0 bits (4)
+        //         than 1 bits (3), so keep only the 4 numbers with a 0 in the second position:
+        //         10110, 10111, 10101, and 10000.
+        //     In the third position, three of the four numbers have a 1, so keep those three:
+        //         10110, 10111, and 10101.
+        //     In the fourth position, two of the three numbers have a 1, so keep those two:
+        //         10110 and 10111.
+        //     In the fifth position, there are an equal number of 0 bits and 1 bits (one each).
+        //     So, to find the oxygen generator rating, keep the number with a 1 in that position:
+        //         10111.
+        //     As there is only one number left, stop; the oxygen generator rating is 10111,
+        //         or 23 in decimal.
+        //
+        let oxygen_generator_rating = report.oxygen_generator_rating().unwrap();
+        // Then, to determine the CO2 scrubber rating value from the same example above:
+        //
+        //     Start again with all 12 numbers and consider only the first bit of each number.
+        //         There are fewer 0 bits (5) than 1 bits (7), so keep only the 5 numbers with a 0
+        //         in the first position: 00100, 01111, 00111, 00010, and 01010.
+        //     Then, consider the second bit of the 5 remaining numbers: there are fewer 1 bits (2)
+        //         than 0 bits (3), so keep only the 2 numbers with a 1 in the second position:
+        //         01111 and 01010.
+        //     In the third position, there are an equal number of 0 bits and 1 bits (one each).
+        //         So, to find the CO2 scrubber rating, keep the number with a 0 in that position:
+        //         01010.
+        //     As there is only one number left, stop; the CO2 scrubber rating is 01010,
+        //         or 10 in decimal.
+        let co2_scrubber_rating = report.co2_scrubber_rating().unwrap();
+
+        assert_eq!(oxygen_generator_rating.to_decimal(), 23);
+        assert_eq!(
+            *oxygen_generator_rating,
+            ReportBits::from_str("10111").unwrap()
+        );
+
+        assert_eq!(co2_scrubber_rating.to_decimal(), 10);
+        assert_eq!(*co2_scrubber_rating, ReportBits::from_str("01010").unwrap())
+    }
+}
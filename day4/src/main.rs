@@ -1,4 +1,6 @@
-use helpers::{read_file_reader, AocError, AocResult, Grid};
+use helpers::{parse_tokens, read_file_reader, AocError, AocResult, Grid, Point};
+#[cfg(test)]
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::io::Read;
 use std::mem::swap;
@@ -8,7 +10,6 @@ use std::str::FromStr;
 // Numbers are chosen at random, and the chosen number is marked on all boards on which it appears.
 // (Numbers may not appear on all boards.) If all numbers in any row or any column of a board are
 // marked, that board wins. (Diagonals don't count.)
-const BINGO_BOARD_GRID: usize = 5;
 type Unit = u32;
 fn main() -> AocResult<()> {
     let mut data = String::new();
@@ -30,24 +31,38 @@ fn main() -> AocResult<()> {
     // You aren't sure how many bingo boards a giant squid could play at once, so rather than waste
     // time counting its arms, the safe thing to do is to figure out which board will win last and
     // choose that one. That way, no matter which boards it picks, it will win for sure.
-    let last_winner = game
-        .play_to_end(&numbers[first_winner.turns..])
-        .ok_or_else(|| {
-            AocError::GridError("With these Numbers, only one Board winds!".to_string())
-        })?;
+    game.reset();
+    let last_winner = game.play_to_end(&numbers).ok_or_else(|| {
+        AocError::GridError("With these Numbers, only one Board winds!".to_string())
+    })?;
     println!("Part 2 Last Game\n{}", last_winner);
 
     Ok(())
 }
 
+// score of the first winner, playing the boards as BitmaskBoards
+#[cfg(test)]
+fn first_bitmask_score(boards: &[BingoBoard], numbers: &[Unit]) -> AocResult<Option<Unit>> {
+    let mut bitmask_boards = boards
+        .iter()
+        .map(BitmaskBoard::try_from)
+        .collect::<AocResult<Vec<_>>>()?;
+    for &number in numbers {
+        for board in bitmask_boards.iter_mut() {
+            board.cross(number);
+            if board.is_bingo() {
+                return Ok(Some(number * board.sum_unmarked()));
+            }
+        }
+    }
+    Ok(None)
+}
+
 fn parse_input(s: &str) -> AocResult<(Vec<Unit>, BingoGame)> {
     if let Some((numbers, boards)) = s.split_once("\n\n") {
         let numbers: Result<Vec<Unit>, _> = numbers.split(',').map(|n| n.parse()).collect();
-        let boards: Result<Vec<_>, _> = boards
-            .split("\n\n")
-            .map(|s| BingoBoard::from_str(s).map(Some))
-            .collect();
-        Ok((numbers?, BingoGame { boards: boards? }))
+        let boards: Result<Vec<_>, _> = boards.split("\n\n").map(BingoBoard::from_str).collect();
+        Ok((numbers?, BingoGame::new(boards?)))
     } else {
         Err(AocError::ParseStructError(
             "Could not split numbers from boards".to_string(),
@@ -56,13 +71,26 @@ fn parse_input(s: &str) -> AocResult<(Vec<Unit>, BingoGame)> {
 }
 
 pub struct BingoGame {
+    // boards that already won are taken out of the game
     boards: Vec<Option<BingoBoard>>,
+    // the boards as they were parsed, used to reset the game
+    original_boards: Vec<BingoBoard>,
+    allow_diagonals: bool,
 }
 
 pub struct Winner {
     turns: usize,
     winning_number: Unit,
     winning_board: BingoBoard,
+    winning_line: WinLine,
+}
+
+// Diagonal(0) runs from the top left corner, Diagonal(1) from the top right corner
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WinLine {
+    Row(usize),
+    Col(usize),
+    Diagonal(usize),
 }
 
 impl Display for Winner {
@@ -70,31 +98,61 @@ impl Display for Winner {
         let sum_unmarked = self.winning_board.sum_unmarked();
         writeln!(
             f,
-            "Game took {} turns\nSum of unmarked fields: {}\nWinning number {}\nProduct:{}",
+            "Game took {} turns\nWinning line {:?}\nSum of unmarked fields: {}\nWinning number {}\nProduct:{}",
             self.turns,
+            self.winning_line,
             sum_unmarked,
             self.winning_number,
             self.winning_number * sum_unmarked
-        )
+        )?;
+        write!(f, "{}", self.winning_board)
     }
 }
 
 impl BingoGame {
+    // diagonals don't count by default
+    pub fn new(boards: Vec<BingoBoard>) -> Self {
+        Self::with_rules(boards, false)
+    }
+
+    pub fn with_rules(boards: Vec<BingoBoard>, allow_diagonals: bool) -> Self {
+        BingoGame {
+            boards: boards.iter().cloned().map(Some).collect(),
+            original_boards: boards,
+            allow_diagonals,
+        }
+    }
+
+    // uncrosses all fields and puts the boards that already won back into the game
+    pub fn reset(&mut self) {
+        self.boards = self.original_boards.iter().cloned().map(Some).collect();
+    }
+
     // play bingo until all boards won
     // returns the last winning board
-    pub fn play_to_end(&mut self, mut numbers: &[Unit]) -> Option<Winner> {
-        let mut last_winner = None;
-        while let Some(winner) = self.play(numbers) {
-            numbers = &numbers[winner.turns..];
-            last_winner = Some(winner);
+    pub fn play_to_end(&mut self, numbers: &[Unit]) -> Option<Winner> {
+        self.play_all(numbers).pop()
+    }
+
+    // play bingo until all boards won
+    // returns every winner in the order they won, turns are counted from the start of numbers
+    // boards that never win are left out
+    pub fn play_all(&mut self, numbers: &[Unit]) -> Vec<Winner> {
+        let mut winners = Vec::new();
+        let mut offset = 0;
+        // the winning number is played again, other boards might win with it as well
+        while let Some(mut winner) = self.play(&numbers[offset..]) {
+            offset += winner.turns;
+            winner.turns = offset;
+            winners.push(winner);
         }
-        last_winner
+        winners
     }
     // returns Some(idx) of the winning board
     // None if nobody wins
     pub fn play(&mut self, numbers: &[Unit]) -> Option<Winner> {
         for (i, &n) in numbers.iter().enumerate() {
-            if let Some(idx) = self.play_number(n) {
+            if let Some((idx, winning_line)) = self.play_number(n) {
                 let mut winner = None;
                 swap(&mut self.boards[idx], &mut winner);
 
@@ -102,18 +160,19 @@ impl BingoGame {
                     turns: i,
                     winning_number: n,
                     winning_board: winner.unwrap(),
+                    winning_line,
                 });
             }
         }
         None
     }
 
-    fn play_number(&mut self, number: Unit) -> Option<usize> {
+    fn play_number(&mut self, number: Unit) -> Option<(usize, WinLine)> {
         for (i, board) in self.boards.iter_mut().enumerate() {
             if let Some(board) = board {
                 board.cross(number);
-                if board.is_bingo() {
-                    return Some(i);
+                if let Some(winning_line) = board.winning_line(self.allow_diagonals) {
+                    return Some((i, winning_line));
                 }
             }
         }
@@ -127,6 +186,7 @@ enum BingoField {
     Crossed(Unit),
 }
 
+#[derive(Clone)]
 pub struct BingoBoard {
     data: Grid<BingoField>,
 }
@@ -142,16 +202,28 @@ impl BingoBoard {
         }
     }
 
-    fn is_bingo(&self) -> bool {
-        (0..BINGO_BOARD_GRID).any(|i| {
-            self.data
-                .iter_col(i)
-                .all(|f| matches!(f.value, BingoField::Crossed(_)))
-                || self
-                    .data
-                    .iter_row(i)
-                    .all(|f| matches!(f.value, BingoField::Crossed(_)))
-        })
+    // returns the first completed line, rows before columns before diagonals
+    // diagonals only count if allowed, boards are always square
+    fn winning_line(&self, allow_diagonals: bool) -> Option<WinLine> {
+        let crossed = |f: Point<'_, BingoField>| matches!(f.value, BingoField::Crossed(_));
+        let last = self.data.column_count() - 1;
+
+        if let Some(y) = (0..self.data.row_count()).find(|&y| self.data.iter_row(y).all(crossed)) {
+            return Some(WinLine::Row(y));
+        }
+        if let Some(x) = (0..self.data.column_count()).find(|&x| self.data.iter_col(x).all(crossed))
+        {
+            return Some(WinLine::Col(x));
+        }
+        if allow_diagonals {
+            if self.data.iter_diagonal_down_right((0, 0)).all(crossed) {
+                return Some(WinLine::Diagonal(0));
+            }
+            if self.data.iter_diagonal_down_left((last, 0)).all(crossed) {
+                return Some(WinLine::Diagonal(1));
+            }
+        }
+        None
     }
 
     fn sum_unmarked(&self) -> Unit {
@@ -165,20 +237,137 @@ impl BingoBoard {
     }
 }
 
+// crossed numbers are wrapped in brackets, all columns have the same width
+impl Display for BingoBoard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let number = |field: &BingoField| match *field {
+            BingoField::Open(n) | BingoField::Crossed(n) => n,
+        };
+        let width = self
+            .data
+            .iter()
+            .map(|field| number(field).to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        for y in 0..self.data.row_count() {
+            let row: String = self
+                .data
+                .iter_row(y)
+                .map(|p| match p.value {
+                    BingoField::Open(n) => format!(" {:>width$} ", n, width = width),
+                    BingoField::Crossed(n) => format!("[{:>width$}]", n, width = width),
+                })
+                .collect();
+            writeln!(f, "{}", row.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
 impl FromStr for BingoBoard {
     type Err = AocError;
 
+    // the size of the board is taken from the number of rows, every row has to be that long
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let numbers: Result<Vec<_>, _> = s
-            .split('\n')
-            .flat_map(|line| line.split_ascii_whitespace())
-            .map(|n| n.parse::<Unit>().map(BingoField::Open))
+        let rows = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                Ok(parse_tokens::<Unit>(line)?
+                    .into_iter()
+                    .map(BingoField::Open)
+                    .collect())
+            })
+            .collect::<AocResult<Vec<Vec<_>>>>()?;
+
+        let data = Grid::from_nested(rows)?;
+        if data.column_count() != data.row_count() {
+            return Err(AocError::ParseStructError(format!(
+                "Bingo boards have to be square, got {} columns and {} rows",
+                data.column_count(),
+                data.row_count()
+            )));
+        }
+
+        Ok(BingoBoard { data })
+    }
+}
+
+// Same game as BingoBoard, but crossing a number is a single lookup and bingo is detected by
+// comparing the crossed cells against precomputed masks for every row and column
+#[cfg(test)]
+pub struct BitmaskBoard {
+    cells: HashMap<Unit, usize>,
+    crossed: u32,
+    line_masks: Vec<u32>,
+}
+
+#[cfg(test)]
+impl BitmaskBoard {
+    fn cross(&mut self, number: Unit) {
+        if let Some(idx) = self.cells.get(&number) {
+            self.crossed |= 1 << idx
+        }
+    }
+
+    fn is_bingo(&self) -> bool {
+        self.line_masks.iter().any(|mask| mask & !self.crossed == 0)
+    }
+
+    fn sum_unmarked(&self) -> Unit {
+        self.cells
+            .iter()
+            .filter(|(_, &idx)| self.crossed & (1 << idx) == 0)
+            .map(|(&number, _)| number)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+impl TryFrom<&BingoBoard> for BitmaskBoard {
+    type Error = AocError;
+
+    fn try_from(board: &BingoBoard) -> Result<Self, Self::Error> {
+        let (columns, rows) = (board.data.column_count(), board.data.row_count());
+        if columns * rows > u32::BITS as usize {
+            return Err(AocError::GridError(format!(
+                "A {}x{} board does not fit into a bitmask",
+                columns, rows
+            )));
+        }
+
+        let mut cells = HashMap::new();
+        let mut crossed = 0;
+        for (x, y, field) in board.data.iter_points() {
+            let idx = y * columns + x;
+            let number = match *field {
+                BingoField::Open(number) => number,
+                BingoField::Crossed(number) => {
+                    crossed |= 1 << idx;
+                    number
+                }
+            };
+            if cells.insert(number, idx).is_some() {
+                return Err(AocError::GridError(format!(
+                    "Number {} appears more than once on a board",
+                    number
+                )));
+            }
+        }
+
+        let row_mask = (1 << columns) - 1;
+        let col_mask = (0..rows).fold(0, |mask, y| mask | 1 << (y * columns));
+        let line_masks = (0..rows)
+            .map(|y| row_mask << (y * columns))
+            .chain((0..columns).map(|x| col_mask << x))
             .collect();
 
-        let board = BingoBoard {
-            data: Grid::from_slice(&numbers.unwrap(), BINGO_BOARD_GRID)?,
-        };
-        Ok(board)
+        Ok(BitmaskBoard {
+            cells,
+            crossed,
+            line_masks,
+        })
     }
 }
 
@@ -203,6 +392,9 @@ mod tests {
         // 188 * 24 = 4512.
         assert_eq!(winner.winning_board.sum_unmarked(), 188);
         assert_eq!(winner.winning_number, 24);
+        // At this point, the third board wins because it has at least one complete row or column
+        // of marked numbers (in this case, the entire top row is marked: 14 21 17 24 4).
+        assert_eq!(winner.winning_line, WinLine::Row(0));
     }
 
     #[test]
@@ -223,5 +415,125 @@ mod tests {
         // for a final score of 148 * 13 = 1924.
         assert_eq!(last_winner.winning_board.sum_unmarked(), 148);
         assert_eq!(last_winner.winning_number, 13);
+        assert_eq!(last_winner.winning_line, WinLine::Col(2));
+    }
+
+    #[test]
+    fn example_all_winners() {
+        let data = helpers::read_file_string("day4.testinput").unwrap();
+        let (numbers, mut game) = parse_input(&data).unwrap();
+
+        let winners = game.play_all(&numbers);
+        let first = winners.first().unwrap();
+        let last = winners.last().unwrap();
+
+        assert_eq!(winners.len(), 3);
+        assert_eq!(
+            (first.winning_number, first.winning_board.sum_unmarked()),
+            (24, 188)
+        );
+        assert_eq!(
+            (last.winning_number, last.winning_board.sum_unmarked()),
+            (13, 148)
+        );
+        assert!(winners.windows(2).all(|w| w[0].turns <= w[1].turns));
+        assert!(game.play_all(&numbers).is_empty());
+    }
+
+    #[test]
+    fn replay_after_reset() {
+        let data = helpers::read_file_string("day4.testinput").unwrap();
+        let (numbers, mut game) = parse_input(&data).unwrap();
+
+        let winner = game.play(&numbers).unwrap();
+        game.reset();
+        let replayed = game.play(&numbers).unwrap();
+
+        assert_eq!(replayed.turns, winner.turns);
+        assert_eq!(replayed.winning_number, winner.winning_number);
+        assert_eq!(replayed.winning_line, winner.winning_line);
+        assert_eq!(replayed.winning_board.sum_unmarked(), 188);
+        assert_eq!(game.boards.iter().filter(|b| b.is_some()).count(), 2);
+    }
+
+    #[test]
+    fn small_board_column_bingo() {
+        let mut board = BingoBoard::from_str("1 2 3\n4 5 6\n7 8 9\n").unwrap();
+        let not_square = BingoBoard::from_str("1 2 3\n4 5 6");
+
+        board.cross(2);
+        board.cross(5);
+        let before_last = board.winning_line(false);
+        board.cross(8);
+
+        assert_eq!(before_last, None);
+        assert_eq!(board.winning_line(false), Some(WinLine::Col(1)));
+        assert_eq!(board.sum_unmarked(), 1 + 3 + 4 + 6 + 7 + 9);
+        assert!(not_square.is_err());
+    }
+
+    #[test]
+    fn bitmask_board_matches_grid_board() {
+        let data = helpers::read_file_string("day4.testinput").unwrap();
+        let (numbers, mut game) = parse_input(&data).unwrap();
+        let mut bitmask_boards: Vec<_> = game
+            .boards
+            .iter()
+            .map(|b| BitmaskBoard::try_from(b.as_ref().unwrap()).unwrap())
+            .collect();
+
+        let winner = game.play(&numbers).unwrap();
+        let (bitmask_number, bitmask_board) = numbers
+            .iter()
+            .find_map(|&n| {
+                let idx = bitmask_boards.iter_mut().position(|b| {
+                    b.cross(n);
+                    b.is_bingo()
+                })?;
+                Some((n, idx))
+            })
+            .map(|(n, idx)| (n, &bitmask_boards[idx]))
+            .unwrap();
+
+        assert_eq!(bitmask_number, winner.winning_number);
+        assert_eq!(
+            bitmask_board.sum_unmarked(),
+            winner.winning_board.sum_unmarked()
+        );
+        assert_eq!(bitmask_board.sum_unmarked(), 188);
+        assert_eq!(
+            first_bitmask_score(&game.original_boards, &numbers).unwrap(),
+            Some(4512)
+        );
+        assert_eq!(
+            first_bitmask_score(&game.original_boards, &[]).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn display_crossed_fields() {
+        let mut board = BingoBoard::from_str("1 2 3\n4 15 6\n7 8 9").unwrap();
+
+        board.cross(2);
+        board.cross(15);
+
+        assert_eq!(board.to_string(), "  1 [ 2]  3\n  4 [15]  6\n  7   8   9\n");
+    }
+
+    #[test]
+    fn diagonal_rule() {
+        let board = || BingoBoard::from_str("1 2 3\n4 5 6\n7 8 9").unwrap();
+        let mut default_game = BingoGame::new(vec![board()]);
+        let mut diagonal_game = BingoGame::with_rules(vec![board()], true);
+        let numbers = [3, 5, 7];
+
+        let default_winner = default_game.play(&numbers);
+        let diagonal_winner = diagonal_game.play(&numbers).unwrap();
+
+        assert!(default_winner.is_none());
+        assert_eq!(diagonal_winner.winning_number, 7);
+        assert_eq!(diagonal_winner.turns, 2);
+        assert_eq!(diagonal_winner.winning_line, WinLine::Diagonal(1));
     }
 }
@@ -1,6 +1,7 @@
-use helpers::{read_lines_parse, AocError, AocResult};
+use helpers::{read_lines_parse, AocError, AocResult, Vec2};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::iter::from_fn;
 use std::str::FromStr;
 
 type Unit = i32;
@@ -22,8 +23,13 @@ fn main() -> AocResult<()> {
     // Part 2
     // You still need to determine the number of points where at least two lines overlap.
     // Consider all of the lines. At how many points do at least two lines overlap?
-    let grid = Grid::from_lines(&input);
-    let points_with_overlapping = grid.intersecting_point_count();
+    // the lines cover most of their bounding box, so every point of it is stored
+    let (min, max) = bounding_box(&input)
+        .ok_or_else(|| AocError::ChallengeError("There are no lines".to_string()))?;
+    let width = (max.x - min.x + 1) as usize;
+    let height = (max.y - min.y + 1) as usize;
+    let dense_grid = DenseGrid::from_lines(&input, width, height)?;
+    let points_with_overlapping = dense_grid.intersecting_point_count();
     println!(
         "Number of points with overlapping lines: {}",
         points_with_overlapping
@@ -32,11 +38,7 @@ fn main() -> AocResult<()> {
     Ok(())
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
-struct Point {
-    x: Unit,
-    y: Unit,
-}
+type Point = Vec2<Unit>;
 
 #[derive(Copy, Clone, Debug)]
 struct Line {
@@ -56,6 +58,8 @@ fn get_step_delta(first: Unit, second: Unit) -> Unit {
 }
 
 impl Line {
+    // only 45 degree steps, kept as a reference for bresenham_points
+    #[cfg(test)]
     fn distinct_points(&self) -> impl Iterator<Item = Point> + '_ {
         PointIterator {
             slope: Point {
@@ -67,8 +71,43 @@ impl Line {
             complete: false,
         }
     }
+
+    // rasterizes lines of any slope, for horizontal, vertical and 45 degree lines this yields
+    // the same points as distinct_points
+    fn bresenham_points(&self) -> impl Iterator<Item = Point> {
+        let end = self.end;
+        let step = Point {
+            x: get_step_delta(self.start.x, self.end.x),
+            y: get_step_delta(self.start.y, self.end.y),
+        };
+        let dx = (self.end.x - self.start.x).abs();
+        let dy = -(self.end.y - self.start.y).abs();
+        let mut error = dx + dy;
+        let mut current = Some(self.start);
+
+        from_fn(move || {
+            let point = current?;
+            current = if point == end {
+                None
+            } else {
+                let mut next = point;
+                let doubled_error = 2 * error;
+                if doubled_error >= dy {
+                    error += dy;
+                    next.x += step.x;
+                }
+                if doubled_error <= dx {
+                    error += dx;
+                    next.y += step.y;
+                }
+                Some(next)
+            };
+            Some(point)
+        })
+    }
 }
 
+#[cfg(test)]
 struct PointIterator {
     slope: Point,
     current: Point,
@@ -76,6 +115,7 @@ struct PointIterator {
     complete: bool,
 }
 
+#[cfg(test)]
 impl Iterator for PointIterator {
     type Item = Point;
 
@@ -87,8 +127,7 @@ impl Iterator for PointIterator {
             if self.current.x == self.end.x && self.current.y == self.end.y {
                 self.complete = true;
             }
-            self.current.x += self.slope.x;
-            self.current.y += self.slope.y;
+            self.current = self.current + self.slope;
             next
         }
     }
@@ -113,24 +152,6 @@ impl FromStr for Line {
     }
 }
 
-const POINT_DELIM: char = ',';
-impl FromStr for Point {
-    type Err = AocError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some((x, y)) = s.split_once(POINT_DELIM) {
-            let x = x.parse()?;
-            let y = y.parse()?;
-            Ok(Point { x, y })
-        } else {
-            Err(AocError::ParseStructError(format!(
-                "Missing delimiter '{}' in Point '{}'",
-                POINT_DELIM, s
-            )))
-        }
-    }
-}
-
 // Grid representing the number of lines present at each Point
 struct Grid {
     data: HashMap<Point, usize>,
@@ -140,22 +161,109 @@ impl Grid {
     fn from_straight_lines_only(lines: &[Line]) -> Self {
         Self::construct_grid(straight_lines(lines))
     }
+    // part 2 uses DenseGrid, this is kept to cross check it
+    #[cfg(test)]
     fn from_lines(lines: &[Line]) -> Self {
         Self::construct_grid(lines.iter())
     }
     fn construct_grid<'a>(lines: impl Iterator<Item = &'a Line>) -> Self {
         let mut grid = HashMap::new();
-        for point in lines.flat_map(|l| l.distinct_points()) {
+        for point in lines.flat_map(|l| l.bresenham_points()) {
             let counter = grid.entry(point).or_insert(0);
             *counter += 1;
         }
         Grid { data: grid }
     }
     fn intersecting_point_count(&self) -> usize {
-        self.data.values().filter(|&&n| n > 1).count()
+        self.overlapping_points().len()
+    }
+    // points where at least two lines overlap, in no particular order
+    fn overlapping_points(&self) -> Vec<Point> {
+        self.data
+            .iter()
+            .filter(|(_, &n)| n > 1)
+            .map(|(&point, _)| point)
+            .collect()
+    }
+    // draws the area from (0, 0) to (width - 1, height - 1) like the puzzle description,
+    // points without lines are '.', more than 9 overlapping lines are shown as 9
+    // only used to compare against the diagrams of the puzzle description
+    #[cfg(test)]
+    fn render(&self, width: usize, height: usize) -> String {
+        let mut rendered = String::with_capacity((width + 1) * height);
+        for y in 0..height {
+            for x in 0..width {
+                let point = Point {
+                    x: x as Unit,
+                    y: y as Unit,
+                };
+                rendered.push(match self.data.get(&point) {
+                    None | Some(0) => '.',
+                    Some(&n) => char::from_digit(n.min(9) as u32, 10).unwrap(),
+                });
+            }
+            rendered.push('\n');
+        }
+        rendered
+    }
+    // maps the number of overlapping lines to the number of points covered by that many lines
+    #[cfg(test)]
+    fn overlap_histogram(&self) -> std::collections::BTreeMap<usize, usize> {
+        let mut histogram = std::collections::BTreeMap::new();
+        for &lines in self.data.values() {
+            *histogram.entry(lines).or_insert(0) += 1;
+        }
+        histogram
+    }
+}
+
+// Same as Grid, but every point of the bounding area is stored, which is cheaper for dense inputs
+// The point closest to the origin of all line ends is stored at (0, 0)
+struct DenseGrid {
+    data: helpers::Grid<u32>,
+}
+
+impl DenseGrid {
+    fn from_lines(lines: &[Line], width: usize, height: usize) -> AocResult<Self> {
+        let offset = bounding_box(lines).map_or_else(Point::default, |(min, _)| min);
+        let mut data = helpers::Grid::default(width, height);
+        for point in lines.iter().flat_map(|l| l.bresenham_points()) {
+            let shifted = point - offset;
+            let counter = data
+                .get_mut(shifted.x as usize, shifted.y as usize)
+                .ok_or_else(|| {
+                    AocError::GridError(format!(
+                        "Point {},{} is outside of the {}x{} grid",
+                        point.x, point.y, width, height
+                    ))
+                })?;
+            *counter.value += 1;
+        }
+        Ok(DenseGrid { data })
+    }
+    fn intersecting_point_count(&self) -> usize {
+        self.data.iter().filter(|&&n| n > 1).count()
     }
 }
 
+// (smallest, largest) corner of the area covering all line ends, None if there are no lines
+fn bounding_box(lines: &[Line]) -> Option<(Point, Point)> {
+    let mut ends = lines.iter().flat_map(|l| [l.start, l.end]);
+    let first = ends.next()?;
+    Some(ends.fold((first, first), |(min, max), p| {
+        (
+            Point {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            },
+            Point {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            },
+        )
+    }))
+}
+
 fn straight_lines(lines: &[Line]) -> impl Iterator<Item = &Line> {
     lines
         .iter()
@@ -212,6 +320,102 @@ mod tests {
         assert_eq!(points_with_overlapping, 5)
     }
 
+    #[test]
+    fn example_overlapping_points() {
+        let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
+
+        let grid = Grid::from_straight_lines_only(&input);
+        let points = grid.overlapping_points();
+
+        assert_eq!(points.len(), 5);
+        assert!(points.contains(&Point { x: 0, y: 9 }));
+        assert!(points.contains(&Point { x: 7, y: 4 }));
+        assert!(!points.contains(&Point { x: 8, y: 4 }));
+    }
+
+    #[test]
+    fn example_dense_grid() {
+        let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
+        let straight: Vec<Line> = straight_lines(&input).copied().collect();
+
+        let dense = DenseGrid::from_lines(&input, 10, 10).unwrap();
+        let dense_straight = DenseGrid::from_lines(&straight, 10, 10).unwrap();
+        let too_small = DenseGrid::from_lines(&input, 9, 10);
+
+        assert_eq!(
+            dense.intersecting_point_count(),
+            Grid::from_lines(&input).intersecting_point_count()
+        );
+        assert_eq!(
+            dense_straight.intersecting_point_count(),
+            Grid::from_straight_lines_only(&input).intersecting_point_count()
+        );
+        assert!(too_small.is_err());
+
+        // 2:1 slope, crosses the horizontal line at 1,1 and 3,1
+        let sloped: Vec<Line> = ["0,0 -> 4,2", "0,1 -> 4,1"]
+            .iter()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        let dense_sloped = DenseGrid::from_lines(&sloped, 5, 3).unwrap();
+        assert_eq!(dense_sloped.intersecting_point_count(), 2);
+        assert_eq!(
+            dense_sloped.intersecting_point_count(),
+            Grid::from_lines(&sloped).intersecting_point_count()
+        );
+    }
+
+    #[test]
+    fn example_bounding_box() {
+        let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
+        let shifted = [Line::from_str("3,8 -> 5,2").unwrap()];
+
+        assert_eq!(
+            bounding_box(&input),
+            Some((Point { x: 0, y: 0 }, Point { x: 9, y: 9 }))
+        );
+        assert_eq!(
+            bounding_box(&shifted),
+            Some((Point { x: 3, y: 2 }, Point { x: 5, y: 8 }))
+        );
+        assert_eq!(bounding_box(&[]), None);
+    }
+
+    #[test]
+    fn example_render() {
+        let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
+
+        let grid = Grid::from_straight_lines_only(&input);
+
+        // So, the horizontal and vertical lines from the above list would produce the following
+        // diagram:
+        assert_eq!(
+            grid.render(10, 10),
+            ".......1..\n\
+             ..1....1..\n\
+             ..1....1..\n\
+             .......1..\n\
+             .112111211\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             ..........\n\
+             222111....\n"
+        );
+    }
+
+    #[test]
+    fn example_overlap_histogram() {
+        let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
+
+        let grid = Grid::from_straight_lines_only(&input);
+        let histogram = grid.overlap_histogram();
+
+        assert_eq!(histogram.get(&1), Some(&16));
+        assert_eq!(histogram.get(&2), Some(&5));
+        assert_eq!(histogram.len(), 2);
+    }
+
     #[test]
     fn example_part2_diagonal_line_points() {
         //An entry like 1,1 -> 3,3 covers points 1,1, 2,2, and 3,3.
@@ -246,6 +450,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn point_arithmetic() {
+        let a = Point { x: 1, y: 5 };
+        let b = Point { x: 4, y: 3 };
+
+        assert_eq!(a + b, Point { x: 5, y: 8 });
+        assert_eq!(a - b, Point { x: -3, y: 2 });
+        assert_eq!(a.manhattan(&b), 5);
+        assert_eq!(b.manhattan(&a), 5);
+        assert_eq!(a.manhattan(&a), 0);
+    }
+
+    #[test]
+    fn bresenham_sloped_line() {
+        let line = Line::from_str("0,0 -> 4,2").unwrap();
+        let reversed = Line::from_str("4,2 -> 0,0").unwrap();
+        let diagonal = Line::from_str("9,7 -> 7,9").unwrap();
+
+        let points: Vec<_> = line.bresenham_points().collect();
+        let mut reversed_points: Vec<_> = reversed.bresenham_points().collect();
+        reversed_points.reverse();
+
+        assert_eq!(
+            points,
+            &[
+                Point { x: 0, y: 0 },
+                Point { x: 1, y: 1 },
+                Point { x: 2, y: 1 },
+                Point { x: 3, y: 2 },
+                Point { x: 4, y: 2 }
+            ]
+        );
+        assert_eq!(reversed_points.len(), 5);
+        assert!(diagonal.bresenham_points().eq(diagonal.distinct_points()));
+    }
+
     #[test]
     fn example_part2_overlapping() {
         let input: Vec<Line> = read_lines_parse("day5.testinput").unwrap();
@@ -1,106 +1,207 @@
-use helpers::AocResult;
+use helpers::{time_it, AocError, AocResult};
 use std::fmt::{Display, Formatter};
-use std::io::Read;
-use std::time::Instant;
 
 type Unit = u64;
+// after spawning, a fish takes this many days to spawn again
 const PARENT_REPRODUCTION_DAYS: usize = 7;
+// a newborn fish takes this many days to spawn for the first time
+const NEWBORN_REPRODUCTION_DAYS: usize = 9;
 
 fn main() -> AocResult<()> {
-    let start = Instant::now();
-    let mut input = String::new();
-    helpers::read_file_reader("day6/day6.input")?.read_to_string(&mut input)?;
-
-    let numbers: Result<Vec<Unit>, _> = input
-        .lines()
-        .flat_map(|l| l.split(','))
-        .map(|n| n.parse())
-        .collect();
+    let (result, duration) = time_it(solve);
+    println!("Took: {:?}", duration);
+    result
+}
 
-    let mut game = GameOfLanternfish::from_numbers(&numbers?);
+fn solve() -> AocResult<()> {
+    let numbers: Vec<Unit> = helpers::read_csv_numbers("day6/day6.input")?;
+
+    let mut game = GameOfLanternfish::from_numbers(&numbers)?;
 
     // Part 1:
     // How many lanternfish would there be after 80 days?
     let day1_days = 80;
-    for _ in 0..day1_days {
-        game.advance_one_day();
-        // println!("{}", game);
-    }
-    println!("Count of Fish after {} days: {}", day1_days, game.count());
+    game.advance_n_days(day1_days);
+    let count = game.count_checked().ok_or_else(|| {
+        AocError::ChallengeError(format!("Fish count after {} days overflows", day1_days))
+    })?;
+    // println!("{}", game);
+    println!("Count of Fish after {} days: {}", day1_days, count);
 
     // Part 2:
     // How many lanternfish would there be after 256 days?
     let day2_days = 256;
-    for _ in 0..day2_days - day1_days {
-        game.advance_one_day();
-        // println!("{}", game);
-    }
-    println!("Count of Fish after {} days: {}", day2_days, game.count());
-    let duration = start.elapsed();
-    println!("Took: {:?}", duration);
+    let count = game.count_after(day2_days - day1_days).ok_or_else(|| {
+        AocError::ChallengeError(format!("Fish count after {} days overflows", day2_days))
+    })?;
+    println!("Count of Fish after {} days: {}", day2_days, count);
 
     Ok(())
 }
 
 struct GameOfLanternfish {
+    // number of fish per internal timer, rotated such that timer 0 is at zero_day_bracket
+    fishes: Vec<Unit>,
     zero_day_bracket: usize,
-    fishes: [Unit; PARENT_REPRODUCTION_DAYS],
-    seven_day_fishes: Unit,
-    eigth_day_fishes: Unit,
-    newborn_fishes: Unit,
+    parent_cycle: usize,
+    // set once a bucket overflowed, the buckets are saturated from then on
+    overflowed: bool,
 }
 
 impl GameOfLanternfish {
-    fn from_numbers(numbers: &[Unit]) -> Self {
-        let mut game = GameOfLanternfish {
-            zero_day_bracket: 0,
-            fishes: Default::default(),
-            seven_day_fishes: 0,
-            eigth_day_fishes: 0,
-            newborn_fishes: 0,
-        };
+    fn from_numbers(numbers: &[Unit]) -> AocResult<Self> {
+        Self::with_cycles(PARENT_REPRODUCTION_DAYS, NEWBORN_REPRODUCTION_DAYS, numbers)
+    }
 
-        for n in numbers {
-            game.fishes[*n as usize] += 1
+    // parent and maturation are the number of days between spawns of grown and newborn fish
+    fn with_cycles(parent: usize, maturation: usize, initial: &[Unit]) -> AocResult<Self> {
+        if parent == 0 || parent > maturation {
+            return Err(AocError::ChallengeError(format!(
+                "Parent cycle of {} days must be between 1 and the maturation cycle of {} days",
+                parent, maturation
+            )));
         }
 
-        game
+        let mut fishes = vec![0; maturation];
+        for &n in initial {
+            let bucket = fishes.get_mut(n as usize).ok_or_else(|| {
+                AocError::ParseStructError(format!(
+                    "Timer {} is out of range for a maturation cycle of {} days",
+                    n, maturation
+                ))
+            })?;
+            *bucket += 1
+        }
+
+        Ok(GameOfLanternfish {
+            fishes,
+            zero_day_bracket: 0,
+            parent_cycle: parent,
+            overflowed: false,
+        })
     }
 
-    fn new_parent_day_index(&self) -> usize {
-        (self.zero_day_bracket + PARENT_REPRODUCTION_DAYS) % PARENT_REPRODUCTION_DAYS
+    fn bucket_index(&self, timer: usize) -> usize {
+        (self.zero_day_bracket + timer) % self.fishes.len()
     }
 
     fn advance_one_day(&mut self) {
-        self.fishes[self.new_parent_day_index()] += self.seven_day_fishes;
-
-        self.seven_day_fishes = self.eigth_day_fishes;
-        self.eigth_day_fishes = self.newborn_fishes;
+        let spawning = self.fishes[self.zero_day_bracket];
+        // the spawning fish stay in their bucket, which now holds the newborns
+        self.zero_day_bracket = self.bucket_index(1);
+        let parent_idx = self.bucket_index(self.parent_cycle - 1);
+        match self.fishes[parent_idx].checked_add(spawning) {
+            Some(fishes) => self.fishes[parent_idx] = fishes,
+            None => {
+                self.overflowed = true;
+                self.fishes[parent_idx] = Unit::MAX;
+            }
+        }
+    }
 
-        self.zero_day_bracket = (self.zero_day_bracket + 1) % PARENT_REPRODUCTION_DAYS;
-        self.newborn_fishes = self.fishes[self.zero_day_bracket]
+    // returns the count after advancing, which has to fit into Unit
+    fn advance_n_days(&mut self, n: usize) -> Unit {
+        for _ in 0..n {
+            self.advance_one_day();
+        }
+        self.count()
     }
 
     fn count(&self) -> Unit {
-        self.seven_day_fishes + self.eigth_day_fishes + self.fishes.iter().sum::<Unit>()
+        self.fishes.iter().sum()
+    }
+
+    // count widened to u128, None if a bucket overflowed while advancing
+    fn count_checked(&self) -> Option<u128> {
+        if self.overflowed {
+            return None;
+        }
+        self.fishes
+            .iter()
+            .try_fold(0u128, |sum, &fishes| sum.checked_add(u128::from(fishes)))
+    }
+
+    // number of fish per internal timer, starting at timer 0
+    // has as many buckets as the maturation cycle has days, 9 with the default rules
+    fn buckets(&self) -> Vec<Unit> {
+        let mut buckets = self.fishes.clone();
+        buckets.rotate_left(self.zero_day_bracket);
+        buckets
+    }
+
+    // number of fish after another days, without advancing the game
+    // the transition of one day is a linear map on the buckets, which is raised to the power of days
+    // None if the count, or any intermediate product, doesn't fit into u128
+    // or if a bucket already overflowed while advancing
+    fn count_after(&self, days: usize) -> Option<u128> {
+        if self.overflowed {
+            return None;
+        }
+        let size = self.fishes.len();
+        let mut transition = vec![vec![0; size]; size];
+        for timer in 1..size {
+            transition[timer - 1][timer] = 1;
+        }
+        transition[size - 1][0] += 1;
+        transition[self.parent_cycle - 1][0] += 1;
+
+        let transition = matrix_power(&transition, days)?;
+        let mut count: u128 = 0;
+        for (from, fishes) in self.buckets().into_iter().enumerate() {
+            for row in &transition {
+                count = count.checked_add(row[from].checked_mul(u128::from(fishes))?)?;
+            }
+        }
+        Some(count)
+    }
+}
+
+// square matrix, indexed [row][column]
+type Matrix = Vec<Vec<u128>>;
+
+// None if any entry overflows
+fn matrix_multiply(a: &Matrix, b: &Matrix) -> Option<Matrix> {
+    let size = a.len();
+    (0..size)
+        .map(|row| {
+            (0..size)
+                .map(|col| {
+                    (0..size).try_fold(0u128, |sum, i| {
+                        sum.checked_add(a[row][i].checked_mul(b[i][col])?)
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// exponentiation by squaring, None if any entry overflows
+fn matrix_power(matrix: &Matrix, mut exponent: usize) -> Option<Matrix> {
+    let size = matrix.len();
+    let mut result: Matrix = (0..size)
+        .map(|row| (0..size).map(|col| u128::from(row == col)).collect())
+        .collect();
+    let mut base = matrix.clone();
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = matrix_multiply(&result, &base)?;
+        }
+        exponent /= 2;
+        if exponent > 0 {
+            base = matrix_multiply(&base, &base)?;
+        }
     }
+    Some(result)
 }
 
 impl Display for GameOfLanternfish {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "zero_day_idx: {}    ", self.zero_day_bracket)?;
 
-        for i in 0..self.fishes.len() {
-            let idx = (self.zero_day_bracket + i) % PARENT_REPRODUCTION_DAYS;
-            write!(f, "{},", self.fishes[idx])?
+        for fishes in self.buckets() {
+            write!(f, "{},", fishes)?
         }
 
-        write!(
-            f,
-            "{},{} => {}",
-            self.seven_day_fishes, self.eigth_day_fishes, self.newborn_fishes
-        )?;
-
         Ok(())
     }
 }
@@ -113,34 +214,132 @@ mod tests {
     fn example_part1() {
         // This list means that the first fish has an internal timer of 3, the second fish has an
         // internal timer of 4, and so on until the fifth fish, which has an internal timer of 2.
-        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]);
+        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
 
-        for _ in 0..18 {
-            game.advance_one_day();
-        }
-        let day_18_count = game.count();
+        let day_18_count = game.advance_n_days(18);
+        let day_18_buckets = game.buckets();
 
-        for _ in 0..80 - 18 {
-            game.advance_one_day();
-        }
-        let day_80_count = game.count();
+        let day_80_count = game.advance_n_days(80 - 18);
+        let day_80_fresh_count = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2])
+            .unwrap()
+            .advance_n_days(80);
 
         // After 18 days: 6,0,6,4,5,6,0,1,1,2,6,0,1,1,1,2,2,3,3,4,6,7,8,8,8,8
         // In this example, after 18 days, there are a total of 26 fish.
         assert_eq!(day_18_count, 26);
+        // timers 0 to 8 of the listing above
+        assert_eq!(day_18_buckets, [3, 5, 3, 2, 2, 1, 5, 1, 4]);
         // After 80 days, there would be a total of 5934.
         assert_eq!(day_80_count, 5934);
+        assert_eq!(day_80_fresh_count, 5934);
     }
 
     #[test]
     fn example_part2() {
-        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]);
+        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
 
         // After 256 days in the example above, there would be a total of 26984457539 lanternfish!
+        let count = game.advance_n_days(256);
+
+        assert_eq!(count, 26984457539);
+    }
+
+    #[test]
+    fn fast_count_matches_simulation() {
+        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
+        let mut other = GameOfLanternfish::with_cycles(2, 4, &[0, 3, 1]).unwrap();
+        let other_fast = other.count_after(37);
+
+        assert_eq!(game.count_after(18), Some(26));
+        assert_eq!(game.count_after(80), Some(5934));
+        assert_eq!(game.count_after(256), Some(26984457539));
+        for day in 0..=256 {
+            assert_eq!(game.count_after(0), Some(u128::from(game.count())));
+            assert_eq!(
+                game.count_after(256 - day),
+                Some(26984457539),
+                "day {}",
+                day
+            );
+            game.advance_one_day();
+        }
+        assert_eq!(other_fast, Some(u128::from(other.advance_n_days(37))));
+    }
+
+    #[test]
+    fn fast_count_overflow() {
+        let game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
+
+        // the count grows by roughly 9% a day, which exceeds u128 after about 1000 days
+        assert!(game.count_after(900).unwrap() > u128::from(u64::MAX));
+        assert_eq!(game.count_after(1100), None);
+        assert_eq!(game.count_after(100_000), None);
+    }
+
+    #[test]
+    fn checked_count() {
+        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
+
         for _ in 0..256 {
             game.advance_one_day();
         }
+        let day_256_count = game.count_checked();
+        // the total no longer fits into Unit, but every single bucket still does
+        for _ in 256..490 {
+            game.advance_one_day();
+        }
+        let day_490_count = game.count_checked();
+
+        assert_eq!(day_256_count, Some(26984457539));
+        assert!(day_490_count.unwrap() > u128::from(Unit::MAX));
+        assert_eq!(day_490_count, game.count_after(0));
+    }
+
+    #[test]
+    fn checked_count_bucket_overflow() {
+        let mut game = GameOfLanternfish::from_numbers(&[3, 4, 3, 1, 2]).unwrap();
+
+        let mut last_day = None;
+        for day in 1..=600 {
+            game.advance_one_day();
+            if game.count_checked().is_some() {
+                last_day = Some(day);
+            }
+        }
 
-        assert_eq!(game.count(), 26984457539);
+        // single buckets exceed u64 later than the total, which does after 490 days
+        assert!(last_day.unwrap() > 490);
+        assert!(last_day.unwrap() < 600);
+        assert_eq!(game.count_checked(), None);
+        assert!(game.buckets().contains(&Unit::MAX));
+        // the saturated buckets must not be used as a starting point
+        assert_eq!(game.count_after(0), None);
+        assert_eq!(game.count_after(10), None);
+    }
+
+    #[test]
+    fn other_cycles() {
+        let (parent, maturation, initial) = (2, 4, [0, 3, 1]);
+        let mut game = GameOfLanternfish::with_cycles(parent, maturation, &initial).unwrap();
+        // every fish simulated on its own
+        let mut timers: Vec<Unit> = initial.to_vec();
+
+        for _ in 0..20 {
+            game.advance_one_day();
+            let mut newborns = 0;
+            for timer in timers.iter_mut() {
+                if *timer == 0 {
+                    *timer = parent as Unit - 1;
+                    newborns += 1;
+                } else {
+                    *timer -= 1;
+                }
+            }
+            timers.extend(std::iter::repeat_n(maturation as Unit - 1, newborns));
+
+            assert_eq!(game.count(), timers.len() as Unit);
+        }
+        assert!(GameOfLanternfish::with_cycles(3, 2, &[]).is_err());
+        assert!(GameOfLanternfish::with_cycles(2, 4, &[4]).is_err());
     }
 }
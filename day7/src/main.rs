@@ -1,69 +1,162 @@
-use helpers::AocResult;
+use helpers::{time_it, AocError, AocResult};
 use itertools::Itertools;
-use itertools::MinMaxResult::MinMax;
 use std::io::Read;
-use std::time::Instant;
 
 type Unit = i32;
 
 fn main() -> AocResult<()> {
-    let start = Instant::now();
+    let (result, duration) = time_it(solve);
+    println!("Took: {:?}", duration);
+    result
+}
+
+fn solve() -> AocResult<()> {
     let mut input = String::new();
     helpers::read_file_reader("day7/day7.input")?.read_to_string(&mut input)?;
 
-    let numbers: Result<Vec<Unit>, _> = input
-        .lines()
-        .flat_map(|l| l.split(','))
-        .map(|n| n.parse())
-        .collect();
-
-    let numbers = numbers?;
+    let numbers = parse_positions(&input)?;
     let min_diff = minimize_difference(&numbers);
     println! {"Sum of minimum differences: {:?}", min_diff};
 
     let min_diff_exp = min_diff_exponential(&numbers);
     println! {"Sum of exponential minimum differences: {:?}", min_diff_exp};
 
-    println!("Took: {:?}", start.elapsed());
     Ok(())
 }
 
-fn minimize_difference(nums: &[Unit]) -> Option<Unit> {
-    if let MinMax(&min, &max) = nums.iter().minmax() {
-        let mut min_sum: Unit = Unit::MAX;
-        for i in min..max + 1 {
-            let sum_diff: Unit = nums.iter().map(|n: &Unit| (*n - i).abs()).sum();
-            min_sum = min_sum.min(sum_diff);
+// positions are separated by commas, but may also span multiple lines
+fn parse_positions(input: &str) -> AocResult<Vec<Unit>> {
+    let mut positions = Vec::new();
+    for (line_number, line) in input.lines().enumerate() {
+        for token in line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|t| !t.is_empty())
+        {
+            let position = token.parse().map_err(|e| {
+                AocError::ParseStructError(format!(
+                    "Invalid position '{}' in line {}: {}",
+                    token,
+                    line_number + 1,
+                    e
+                ))
+            })?;
+            positions.push(position);
         }
-        Some(min_sum)
-    } else {
-        None
     }
+    Ok(positions)
 }
 
-fn min_diff_exponential(nums: &[Unit]) -> Option<Unit> {
-    if let MinMax(&min, &max) = nums.iter().minmax() {
-        let mut min_sum: Unit = Unit::MAX;
-        for i in min..max + 1 {
-            let sum_diff: Unit = nums
+// returns (best_position, min_cost) of moving all crabs to the same position, where cost is
+// the fuel needed to move a single crab by a distance
+// ties are resolved towards the smallest position
+fn minimize_cost<F: Fn(Unit) -> Unit>(nums: &[Unit], cost: F) -> Option<(Unit, Unit)> {
+    let counts: Vec<_> = nums.iter().map(|&n| (n, 1)).collect();
+    minimize_cost_weighted(&counts, cost)
+}
+
+// same as minimize_cost, but counts holds (position, number of crabs at that position)
+fn minimize_cost_weighted<F: Fn(Unit) -> Unit>(
+    counts: &[(Unit, Unit)],
+    cost: F,
+) -> Option<(Unit, Unit)> {
+    let (min, max) = counts
+        .iter()
+        .map(|&(position, _)| position)
+        .minmax()
+        .into_option()?;
+    (min..max + 1)
+        .map(|target| {
+            let total: Unit = counts
                 .iter()
-                .map(|n: &Unit| {
-                    let diff = (*n - i).abs();
-                    (diff * (diff + 1)) / 2
-                })
+                .map(|&(position, crabs)| crabs * cost((position - target).abs()))
                 .sum();
-            min_sum = min_sum.min(sum_diff);
+            (target, total)
+        })
+        .min_by_key(|&(_, total)| total)
+}
+
+fn minimize_difference(nums: &[Unit]) -> Option<Unit> {
+    minimize_cost(nums, |distance| distance).map(|(_, total)| total)
+}
+
+// moving a crab by n costs 1 + 2 + ... + n
+fn min_diff_exponential(nums: &[Unit]) -> Option<Unit> {
+    minimize_cost(nums, |distance| (distance * (distance + 1)) / 2).map(|(_, total)| total)
+}
+
+#[cfg(test)]
+fn total_cost<F: Fn(Unit) -> Unit>(nums: &[Unit], position: Unit, cost: F) -> Unit {
+    nums.iter().map(|n| cost((n - position).abs())).sum()
+}
+
+// the sum of distances is minimal at the median
+#[cfg(test)]
+fn min_diff_linear(nums: &[Unit]) -> Option<Unit> {
+    let mut sorted = nums.to_vec();
+    sorted.sort_unstable();
+    let median = *sorted.get(sorted.len().checked_sub(1)? / 2)?;
+    Some(total_cost(nums, median, |distance| distance))
+}
+
+// the triangular cost is minimal within 1/2 of the mean, so only the two integer positions
+// around the mean have to be checked
+#[cfg(test)]
+fn min_diff_exponential_fast(nums: &[Unit]) -> Option<Unit> {
+    if nums.is_empty() {
+        return None;
+    }
+    let floor_mean = nums.iter().sum::<Unit>().div_euclid(nums.len() as Unit);
+    (floor_mean..=floor_mean + 1)
+        .map(|position| total_cost(nums, position, |distance| distance * (distance + 1) / 2))
+        .min()
+}
+
+// same as minimize_cost, but expects the total cost to be convex in the position, which holds
+// for any cost that is convex in the distance
+// narrows the range down by thirds and scans the last few positions
+#[cfg(test)]
+fn minimize_cost_ternary<F: Fn(Unit) -> Unit>(nums: &[Unit], cost: F) -> Option<(Unit, Unit)> {
+    let (mut low, mut high) = nums.iter().copied().minmax().into_option()?;
+    while high - low > 2 {
+        let third = (high - low) / 3;
+        let (left, right) = (low + third, high - third);
+        // with equal costs the smallest optimal position can't be right of right
+        if total_cost(nums, left, &cost) <= total_cost(nums, right, &cost) {
+            high = right;
+        } else {
+            low = left + 1;
         }
-        Some(min_sum)
-    } else {
-        None
     }
+    (low..=high)
+        .map(|position| (position, total_cost(nums, position, &cost)))
+        .min_by_key(|&(_, total)| total)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn parse_multiple_lines() {
+        let input = "16,1,2,0,4\n2,7, 1,2,14\n";
+
+        let positions = parse_positions(input).unwrap();
+
+        assert_eq!(positions, [16, 1, 2, 0, 4, 2, 7, 1, 2, 14]);
+    }
+
+    #[test]
+    fn parse_invalid_token() {
+        let input = "16,1,2\n0,four,2";
+
+        let error = parse_positions(input).unwrap_err();
+
+        assert!(matches!(
+            error,
+            AocError::ParseStructError(message) if message.contains("'four' in line 2")
+        ));
+    }
+
     #[test]
     fn example_part1() {
         let numbers = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
@@ -85,4 +178,100 @@ mod tests {
         assert_eq!(got_empty, None);
         assert_eq!(got, Some(168));
     }
+
+    #[test]
+    fn example_best_positions() {
+        let numbers = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+
+        let linear = minimize_cost(&numbers, |distance| distance);
+        let triangular = minimize_cost(&numbers, |distance| distance * (distance + 1) / 2);
+        let single = minimize_cost(&[3], |distance| distance);
+
+        // This costs a total of 37 fuel. This is the cheapest possible outcome;
+        assert_eq!(linear, Some((2, 37)));
+        // This costs a total of 168 fuel. This is the new cheapest possible outcome;
+        assert_eq!(triangular, Some((5, 168)));
+        assert_eq!(single, Some((3, 0)));
+        assert_eq!(minimize_cost(&[], |distance| distance), None);
+    }
+
+    #[test]
+    fn fast_solvers_match_brute_force() {
+        let numbers = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        // linear congruential generator, good enough to shuffle some positions
+        let mut seed: u32 = 7;
+        let mut random = move |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+
+        assert_eq!(min_diff_linear(&numbers), Some(37));
+        assert_eq!(min_diff_exponential_fast(&numbers), Some(168));
+        assert_eq!(min_diff_linear(&[]), None);
+        assert_eq!(min_diff_exponential_fast(&[]), None);
+        for _ in 0..100 {
+            let len = random(20) as usize + 1;
+            let nums: Vec<Unit> = (0..len).map(|_| random(200) as Unit - 50).collect();
+
+            assert_eq!(
+                min_diff_linear(&nums),
+                minimize_difference(&nums),
+                "{:?}",
+                nums
+            );
+            assert_eq!(
+                min_diff_exponential_fast(&nums),
+                min_diff_exponential(&nums),
+                "{:?}",
+                nums
+            );
+        }
+    }
+
+    #[test]
+    fn weighted_counts_match_expanded() {
+        let triangular = |distance: Unit| distance * (distance + 1) / 2;
+
+        let weighted = minimize_cost_weighted(&[(1, 3), (16, 1)], |distance| distance);
+        let expanded = minimize_cost(&[1, 1, 1, 16], |distance| distance);
+        let weighted_triangular = minimize_cost_weighted(&[(1, 3), (16, 1)], triangular);
+        let expanded_triangular = minimize_cost(&[1, 1, 1, 16], triangular);
+
+        assert_eq!(weighted, expanded);
+        assert_eq!(weighted, Some((1, 15)));
+        assert_eq!(weighted_triangular, expanded_triangular);
+    }
+
+    #[test]
+    fn ternary_search_matches_brute_force() {
+        let numbers = [16, 1, 2, 0, 4, 2, 7, 1, 2, 14];
+        let linear = |distance: Unit| distance;
+        let triangular = |distance: Unit| distance * (distance + 1) / 2;
+        let mut seed: u32 = 13;
+        let mut random = move |modulus: u32| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % modulus
+        };
+
+        assert_eq!(minimize_cost_ternary(&numbers, linear), Some((2, 37)));
+        assert_eq!(minimize_cost_ternary(&numbers, triangular), Some((5, 168)));
+        assert_eq!(minimize_cost_ternary(&[], linear), None);
+        for _ in 0..100 {
+            let len = random(20) as usize + 1;
+            let nums: Vec<Unit> = (0..len).map(|_| random(200) as Unit - 50).collect();
+
+            assert_eq!(
+                minimize_cost_ternary(&nums, linear),
+                minimize_cost(&nums, linear),
+                "{:?}",
+                nums
+            );
+            assert_eq!(
+                minimize_cost_ternary(&nums, triangular),
+                minimize_cost(&nums, triangular),
+                "{:?}",
+                nums
+            );
+        }
+    }
 }
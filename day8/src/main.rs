@@ -1,5 +1,5 @@
 use helpers::{AocError, AocResult};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 const ONE_SEGMENTS: usize = 2;
@@ -8,6 +8,11 @@ const SEVEN_SEGMENTS: usize = 3;
 const EIGHT_SEGMENTS: usize = 7;
 const TWO_THREE_FIVE_SEGMENTS: usize = 5;
 const ZERO_SIX_NINE_SEGMENTS: usize = 6;
+// segments of the digits 0 to 9 on a display that is wired correctly
+#[cfg(test)]
+const DIGIT_SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
 
 #[derive(Clone, Debug)]
 struct DigitDisplay {
@@ -59,12 +64,13 @@ fn main() -> AocResult<()> {
     // For each entry, determine all of the wire/segment connections and decode the four-digit
     // output values. What do you get if you add up all of the output values?
     let mut sum = 0;
-    for display in displays {
+    for display in &displays {
         sum += display.decode().ok_or_else(|| {
             AocError::ParseStructError(format!("Display could not be solved: {:?}", display))
         })?;
     }
     println!("Sum of output values: {}", sum);
+
     Ok(())
 }
 
@@ -173,22 +179,79 @@ impl DigitDisplay {
     }
 
     pub fn decode(&self) -> Option<usize> {
-        self.solve().map(|solution| {
-            let mut sum = 0;
-            for (i, n) in self.output.iter().rev().enumerate() {
-                let digit = solution
+        let digits = digits_by_key(&self.solve()?);
+        let mut sum = 0;
+        for (i, n) in self.output.iter().rev().enumerate() {
+            let digit = *digits.get(&normalize(n))?;
+            sum += digit * 10_usize.pow(i as u32);
+        }
+        Some(sum)
+    }
+
+    // None if the display can't be solved or the pattern is not one of the solved digits
+    #[cfg(test)]
+    pub fn digit_of(&self, pattern: &Pattern) -> Option<usize> {
+        digits_by_key(&self.solve()?)
+            .get(&normalize(pattern))
+            .copied()
+    }
+
+    // maps each scrambled wire to the segment it is actually connected to
+    // every segment is lit in a different set of digits, which identifies its wire
+    #[cfg(test)]
+    pub fn segment_mapping(&self) -> Option<HashMap<char, char>> {
+        let solution = self.solve()?;
+
+        let mut mapping = HashMap::new();
+        for &wire in &solution[8] {
+            let lit_digits = solution.iter().map(|p| p.contains(&wire));
+            let segment = ('a'..='g').find(|&segment| {
+                DIGIT_SEGMENTS
                     .iter()
-                    .enumerate()
-                    .find(|(_, p)| *p == n)
-                    .map(|(n, _)| n)
-                    .unwrap();
-                sum += digit * 10_usize.pow(i as u32);
-            }
-            sum
-        })
+                    .map(|digit| digit.contains(segment))
+                    .eq(lit_digits.clone())
+            })?;
+            mapping.insert(wire, segment);
+        }
+        Some(mapping)
     }
 }
 
+// scrambles the segments of each digit the way the mapping from wire to segment does
+// None if a digit is not between 0 and 9 or the mapping doesn't cover all of its segments
+#[cfg(test)]
+fn encode(digits: &[usize], mapping: &HashMap<char, char>) -> Option<Vec<Pattern>> {
+    let wires: HashMap<char, char> = mapping
+        .iter()
+        .map(|(&wire, &segment)| (segment, wire))
+        .collect();
+    digits
+        .iter()
+        .map(|&digit| {
+            DIGIT_SEGMENTS
+                .get(digit)?
+                .chars()
+                .map(|segment| wires.get(&segment).copied())
+                .collect()
+        })
+        .collect()
+}
+
+// sorted segments of a pattern, equal patterns have equal keys
+fn normalize(p: &Pattern) -> String {
+    let mut segments: Vec<char> = p.iter().copied().collect();
+    segments.sort_unstable();
+    segments.into_iter().collect()
+}
+
+fn digits_by_key(solution: &Solution) -> HashMap<String, usize> {
+    solution
+        .iter()
+        .enumerate()
+        .map(|(digit, p)| (normalize(p), digit))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +305,84 @@ mod tests {
         assert_eq!(decoded, 5353)
     }
 
+    #[test]
+    fn test_digit_of() {
+        let example = DigitDisplay::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+        )
+        .unwrap();
+        let pattern = |s: &str| -> Pattern { s.chars().collect() };
+
+        let output_digits: Vec<_> = example.output.iter().map(|p| example.digit_of(p)).collect();
+
+        assert_eq!(output_digits, [Some(5), Some(3), Some(5), Some(3)]);
+        // acedgfb: 8, dab: 7, cagedb: 0, ab: 1
+        assert_eq!(example.digit_of(&pattern("acedgfb")), Some(8));
+        assert_eq!(example.digit_of(&pattern("dab")), Some(7));
+        assert_eq!(example.digit_of(&pattern("cagedb")), Some(0));
+        assert_eq!(example.digit_of(&pattern("ba")), Some(1));
+        assert_eq!(example.digit_of(&pattern("abc")), None);
+    }
+
+    #[test]
+    fn test_segment_mapping() {
+        let example = DigitDisplay::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+        )
+        .unwrap();
+
+        let mapping = example.segment_mapping().unwrap();
+        let segments: HashSet<char> = mapping.values().copied().collect();
+
+        //  dddd
+        // e    a
+        // e    a
+        //  ffff
+        // g    b
+        // g    b
+        //  cccc
+        let expected: HashMap<char, char> = "deafgbc".chars().zip('a'..='g').collect();
+        assert_eq!(mapping, expected);
+        assert_eq!(mapping.len(), 7);
+        assert_eq!(segments, ('a'..='g').collect());
+    }
+
+    #[test]
+    fn test_normalize() {
+        let cba: Pattern = "cba".chars().collect();
+        let gcdfa: Pattern = "gcdfa".chars().collect();
+        let fbcad: Pattern = "fbcad".chars().collect();
+        let dcfag: Pattern = "dcfag".chars().collect();
+
+        assert_eq!(normalize(&cba), "abc");
+        assert_eq!(normalize(&gcdfa), normalize(&dcfag));
+        assert_ne!(normalize(&gcdfa), normalize(&fbcad));
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let example = DigitDisplay::from_str(
+            "acedgfb cdfbe gcdfa fbcad dab cefabd cdfgeb eafb cagedb ab | cdfeb fcadb cdfeb cdbaf",
+        )
+        .unwrap();
+        let mapping = example.segment_mapping().unwrap();
+        let digits = [9, 0, 4, 7];
+
+        let encoded = DigitDisplay {
+            signal_patterns: encode(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9], &mapping).unwrap(),
+            output: encode(&digits, &mapping).unwrap(),
+        };
+        let mut incomplete = mapping.clone();
+        incomplete.retain(|_, &mut segment| segment != 'g');
+
+        assert_eq!(encode(&[5, 3, 5, 3], &mapping), Some(example.output));
+        assert_eq!(encode(&[10], &mapping), None);
+        assert_eq!(encode(&[1, 8], &incomplete), None);
+        assert_eq!(encode(&[1, 7], &incomplete).map(|p| p.len()), Some(2));
+        assert_eq!(encoded.segment_mapping(), Some(mapping));
+        assert_eq!(encoded.decode(), Some(9047));
+    }
+
     #[test]
     fn test_part2() {
         let displays: Vec<DigitDisplay> = helpers::read_lines_parse("day8.testinput").unwrap();
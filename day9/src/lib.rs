@@ -0,0 +1,311 @@
+use helpers::{AocResult, Grid};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+
+pub type Unit = u32;
+pub type Point<'a> = helpers::Point<'a, Unit>;
+const BASIN_DELIMITER: Unit = 9;
+
+// Strict low points are lower than all of their neighbours
+// With Plateau, a region of equal heights that is surrounded by higher points counts as one low point
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LowPointMode {
+    Strict,
+    Plateau,
+}
+
+#[derive(Debug)]
+pub struct SmokeBasin {
+    data: Grid<Unit>,
+    mode: LowPointMode,
+}
+
+impl SmokeBasin {
+    pub fn from_input(data: &str) -> AocResult<Self> {
+        let mut numbers = Vec::new();
+        for row in data.lines() {
+            let nums: Vec<_> = row.chars().filter_map(|c| c.to_digit(10)).collect();
+            numbers.push(nums);
+        }
+
+        let data = Grid::from_nested(numbers)?;
+
+        Ok(Self {
+            data,
+            mode: LowPointMode::Strict,
+        })
+    }
+
+    pub fn with_mode(self, mode: LowPointMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    fn is_low(&self, point: &Point) -> bool {
+        match self.mode {
+            LowPointMode::Strict => self.is_low_point(point),
+            LowPointMode::Plateau => self.is_low_point_or_plateau(point),
+        }
+    }
+
+    fn is_low_point(&self, point: &Point) -> bool {
+        if *point.value == BASIN_DELIMITER {
+            return false;
+        }
+        self.data
+            .neighbour_values(point.x, point.y)
+            .all(|other| point.value < other)
+    }
+
+    fn is_low_point_or_plateau(&self, point: &Point) -> bool {
+        self.low_plateau(point).is_some()
+    }
+
+    // all points with the same height connected to point, if they are surrounded by higher points
+    // a single low point is a plateau of size 1
+    fn low_plateau(&self, point: &Point) -> Option<Vec<(usize, usize)>> {
+        if *point.value == BASIN_DELIMITER {
+            return None;
+        }
+        let plateau = self
+            .data
+            .flood_fill(point.x, point.y, |&v| v == *point.value);
+        // equal neighbours are part of the plateau, so all others have to be higher
+        plateau
+            .iter()
+            .all(|&(x, y)| {
+                self.data
+                    .neighbour_values(x, y)
+                    .all(|other| point.value <= other)
+            })
+            .then_some(plateau)
+    }
+
+    // in Plateau mode, only the first point of each plateau is returned
+    pub fn get_low_points(&self) -> Vec<Point<'_>> {
+        let (x_dim, y_dim) = self.data.dimensions();
+
+        let mut low_points = Vec::new();
+        let mut plateaus = HashSet::new();
+        for y in 0..y_dim {
+            for x in 0..x_dim {
+                if plateaus.contains(&(x, y)) {
+                    continue;
+                }
+                if let Some(node) = self.data.get(x, y) {
+                    match self.mode {
+                        LowPointMode::Strict => {
+                            if self.is_low_point(&node) {
+                                low_points.push(node)
+                            }
+                        }
+                        LowPointMode::Plateau => {
+                            if let Some(plateau) = self.low_plateau(&node) {
+                                plateaus.extend(plateau);
+                                low_points.push(node)
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        low_points
+    }
+
+    pub fn get_basin_size(&self, point: &Point) -> Option<usize> {
+        if !self.is_low(point) {
+            return None;
+        }
+
+        let basin = self
+            .data
+            .flood_fill(point.x, point.y, |&v| v < BASIN_DELIMITER);
+        Some(basin.len())
+    }
+
+    // same as get_basin_size, but basins are connected diagonally as well
+    pub fn get_basin_size_8(&self, point: &Point) -> Option<usize> {
+        if !self.is_low(point) {
+            return None;
+        }
+
+        let basin = self
+            .data
+            .flood_fill_surrounding(point.x, point.y, |&v| v < BASIN_DELIMITER);
+        Some(basin.len())
+    }
+
+    // every low point with the size of its basin, biggest basins first
+    pub fn basins(&self) -> Vec<(Point<'_>, usize)> {
+        let mut basins: Vec<_> = self
+            .get_low_points()
+            .into_iter()
+            .filter_map(|p| self.get_basin_size(&p).map(|size| (p, size)))
+            .collect();
+        basins.sort_by_key(|&(_, size)| Reverse(size));
+        basins
+    }
+
+    // basin id of every point, ids are the index of the low point in get_low_points
+    // basin delimiters are None, points reachable from several low points belong to the first
+    pub fn label_basins(&self) -> Grid<Option<usize>> {
+        let (x_dim, y_dim) = self.data.dimensions();
+        let mut labels = Grid::with_default(x_dim, y_dim, None);
+        for (id, low_point) in self.get_low_points().iter().enumerate() {
+            for (x, y) in self
+                .data
+                .flood_fill(low_point.x, low_point.y, |&v| v < BASIN_DELIMITER)
+            {
+                if let Some(label) = labels.get_mut(x, y) {
+                    label.value.get_or_insert(id);
+                }
+            }
+        }
+        labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helpers::read_file_string;
+
+    #[test]
+    fn example_part1() {
+        let input = read_file_string("day9.testinput").unwrap();
+
+        let basin = SmokeBasin::from_input(&input).unwrap();
+        let low_points = basin.get_low_points();
+
+        assert!(low_points.contains(&Point {
+            x: 1,
+            y: 0,
+            value: &1
+        }));
+        assert!(low_points.contains(&Point {
+            x: 9,
+            y: 0,
+            value: &0
+        }));
+        assert!(low_points.contains(&Point {
+            x: 2,
+            y: 2,
+            value: &5
+        }));
+        assert!(low_points.contains(&Point {
+            x: 6,
+            y: 4,
+            value: &5
+        }));
+        assert_eq!(low_points.len(), 4);
+    }
+
+    #[test]
+    fn example_part2() {
+        let input = read_file_string("day9.testinput").unwrap();
+
+        let basin = SmokeBasin::from_input(&input).unwrap();
+        let low_points = basin.get_low_points();
+
+        let basins: Vec<_> = low_points
+            .iter()
+            .filter_map(|b| basin.get_basin_size(b))
+            .collect();
+
+        assert!(basins.contains(&3));
+        assert!(basins.contains(&14));
+        assert_eq!(basins.iter().filter(|&&n| n == 9).count(), 2)
+    }
+
+    #[test]
+    fn example_basin_labels() {
+        let input = read_file_string("day9.testinput").unwrap();
+
+        let basin = SmokeBasin::from_input(&input).unwrap();
+        let labels = basin.label_basins();
+        let distinct: HashSet<_> = labels.iter().flatten().collect();
+        let unlabeled = labels.iter().filter(|l| l.is_none()).count();
+        let delimiters = basin.data.iter().filter(|&&v| v == BASIN_DELIMITER).count();
+
+        assert_eq!(labels.dimensions(), basin.data.dimensions());
+        assert_eq!(distinct.len(), basin.get_low_points().len());
+        assert_eq!(unlabeled, delimiters);
+        // the top left low point is the first one, its basin has size 3
+        assert_eq!(labels.iter().filter(|&&l| l == Some(0)).count(), 3);
+    }
+
+    #[test]
+    fn diagonal_basins() {
+        let basin = SmokeBasin::from_input("199\n919\n991").unwrap();
+        let low_points = basin.get_low_points();
+
+        let sizes: Vec<_> = low_points
+            .iter()
+            .filter_map(|p| basin.get_basin_size(p))
+            .collect();
+        let sizes_8: Vec<_> = low_points
+            .iter()
+            .filter_map(|p| basin.get_basin_size_8(p))
+            .collect();
+
+        assert_eq!(sizes, [1, 1, 1]);
+        assert_eq!(sizes_8, [3, 3, 3]);
+    }
+
+    #[test]
+    fn plateau_low_points() {
+        let input = "99999\n91129\n99999";
+        let strict = SmokeBasin::from_input(input).unwrap();
+        let plateau = SmokeBasin::from_input(input)
+            .unwrap()
+            .with_mode(LowPointMode::Plateau);
+        let example = read_file_string("day9.testinput").unwrap();
+        let example_plateau = SmokeBasin::from_input(&example)
+            .unwrap()
+            .with_mode(LowPointMode::Plateau);
+
+        let low_points = plateau.get_low_points();
+
+        assert!(strict.get_low_points().is_empty());
+        assert_eq!(
+            low_points,
+            [Point {
+                x: 1,
+                y: 1,
+                value: &1
+            }]
+        );
+        assert_eq!(plateau.get_basin_size(&low_points[0]), Some(3));
+        assert_eq!(example_plateau.get_low_points().len(), 4);
+    }
+
+    #[test]
+    fn example_sorted_basins() {
+        let input = read_file_string("day9.testinput").unwrap();
+
+        let basin = SmokeBasin::from_input(&input).unwrap();
+        let basins = basin.basins();
+        let top_three: Vec<_> = basins.iter().take(3).map(|&(_, size)| size).collect();
+
+        assert_eq!(top_three, [14, 9, 9]);
+        assert!(basins.windows(2).all(|w| w[0].1 >= w[1].1));
+        // the middle basin belongs to the low point 5 at (2, 2)
+        assert_eq!((basins[0].0.x, basins[0].0.y), (2, 2));
+        assert_eq!(basins.len(), 4);
+    }
+
+    #[test]
+    fn example_basin_sizes() {
+        let input = read_file_string("day9.testinput").unwrap();
+
+        let basin = SmokeBasin::from_input(&input).unwrap();
+        let mut sizes: Vec<_> = basin
+            .get_low_points()
+            .iter()
+            .filter_map(|p| basin.get_basin_size(p))
+            .collect();
+        sizes.sort_unstable();
+
+        // The top-left basin has size 3, the top-right 9, the middle 14 and the bottom-right 9
+        assert_eq!(sizes, [3, 9, 9, 14]);
+    }
+}
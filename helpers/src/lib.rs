@@ -1,10 +1,14 @@
+use std::collections::{HashSet, VecDeque};
 use std::fmt::{Debug, Formatter};
 use std::fs::File;
 use std::io::{BufRead, BufReader, Read};
+use std::iter::successors;
 use std::mem::swap;
 use std::num::ParseIntError;
-use std::path::Path;
+use std::ops::{Add, Sub};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 use std::{env, io};
 use thiserror::Error;
 
@@ -12,9 +16,9 @@ pub type AocResult<T> = Result<T, AocError>;
 
 #[derive(Error, Debug)]
 pub enum AocError {
-    #[error("io error")]
+    #[error("io error: {0}")]
     IoError(#[from] io::Error),
-    #[error("parse int error")]
+    #[error("parse int error: {0}")]
     ParseIntError(#[from] ParseIntError),
     #[error("parse struct error {0}")]
     ParseStructError(String),
@@ -24,11 +28,49 @@ pub enum AocError {
     ChallengeError(String),
 }
 
+// common shape of a day: parse the puzzle input once, then solve both parts on it
+pub trait Solution {
+    type Input;
+
+    fn parse(s: &str) -> AocResult<Self::Input>;
+    fn part1(input: &Self::Input) -> String;
+    fn part2(input: &Self::Input) -> String;
+
+    // additional lines printed after both parts
+    fn details(_input: &Self::Input) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn run<P: AsRef<Path>>(filename: P) -> AocResult<()> {
+        let input = Self::parse(&read_file_string(filename)?)?;
+        println!("Part 1: {}", Self::part1(&input));
+        println!("Part 2: {}", Self::part2(&input));
+        for line in Self::details(&input) {
+            println!("{}", line);
+        }
+        Ok(())
+    }
+}
+
 pub fn print_current_dir() {
     let path = env::current_dir();
     println!("The current directory is {:?}", path);
 }
 
+// runs f and returns its result together with the time it took
+pub fn time_it<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+// resolves to <workspace root>/<day>/<name>, independent of the current directory
+pub fn input_path(day: &str, name: &str) -> PathBuf {
+    let helpers_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let workspace_root = helpers_dir.parent().unwrap_or(helpers_dir);
+    workspace_root.join(day).join(name)
+}
+
 pub fn read_file_reader<P>(filename: P) -> AocResult<BufReader<File>>
 where
     P: AsRef<Path>,
@@ -41,8 +83,15 @@ pub fn read_file_string<P>(filename: P) -> AocResult<String>
 where
     P: AsRef<Path>,
 {
+    read_string(read_file_reader(filename)?)
+}
+
+pub fn read_string_stdin() -> AocResult<String> {
+    read_string(io::stdin().lock())
+}
+
+fn read_string<R: Read>(mut reader: R) -> AocResult<String> {
     let mut s = String::new();
-    let mut reader = read_file_reader(filename)?;
     reader.read_to_string(&mut s)?;
     Ok(s)
 }
@@ -53,21 +102,199 @@ where
     T: FromStr,
     AocError: From<<T as FromStr>::Err>,
 {
-    let reader = read_file_reader(filename)?;
+    parse_lines_iter(filename)?.collect()
+}
+
+// parses lines lazily, such that the whole input never has to be kept in memory
+pub fn parse_lines_iter<T, P>(filename: P) -> AocResult<impl Iterator<Item = AocResult<T>>>
+where
+    P: AsRef<Path>,
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    Ok(parse_lines(read_file_reader(filename)?))
+}
+
+pub fn read_lines_parse_stdin<T>() -> AocResult<Vec<T>>
+where
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    parse_lines(io::stdin().lock()).collect()
+}
+
+fn parse_lines<T, R>(reader: R) -> impl Iterator<Item = AocResult<T>>
+where
+    R: BufRead,
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    reader.lines().map(|line| Ok(line?.parse()?))
+}
+
+// values separated by any amount of ascii whitespace, including newlines
+pub fn parse_tokens<T>(s: &str) -> AocResult<Vec<T>>
+where
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    let mut parsed = Vec::new();
+    for token in s.split_ascii_whitespace() {
+        parsed.push(token.parse()?);
+    }
+
+    Ok(parsed)
+}
+
+// blocks of lines separated by blank lines, each block is trimmed
+pub fn read_blocks<P>(filename: P) -> AocResult<Vec<String>>
+where
+    P: AsRef<Path>,
+{
+    let input = read_file_string(filename)?.replace("\r\n", "\n");
+    Ok(input
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|b| !b.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+// numbers separated by commas, possibly spanning multiple lines
+// empty fields, e.g. from trailing commas or newlines, are skipped
+pub fn read_csv_numbers<T, P>(filename: P) -> AocResult<Vec<T>>
+where
+    P: AsRef<Path>,
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    let input = read_file_string(filename)?;
     let mut parsed = Vec::new();
-    for line in reader.lines() {
-        let value = line?.parse()?;
-        parsed.push(value);
+    for field in input
+        .lines()
+        .flat_map(|l| l.split(','))
+        .map(str::trim)
+        .filter(|f| !f.is_empty())
+    {
+        parsed.push(field.parse()?);
     }
 
     Ok(parsed)
 }
 
+// owned pair of coordinates, as opposed to Point which borrows a value from a Grid
+#[derive(Copy, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Vec2<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Vec2<T> {
+    pub fn new(x: T, y: T) -> Self {
+        Vec2 { x, y }
+    }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>> Vec2<T> {
+    // sum of the absolute differences of both coordinates, works for unsigned types as well
+    pub fn manhattan(&self, other: &Self) -> T {
+        let distance = |a: T, b: T| if a > b { a - b } else { b - a };
+        distance(self.x, other.x) + distance(self.y, other.y)
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vec2<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Vec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vec2<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+//<x>,<y>
+const VEC2_DELIM: char = ',';
+impl<T> FromStr for Vec2<T>
+where
+    T: FromStr,
+    AocError: From<<T as FromStr>::Err>,
+{
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some((x, y)) = s.split_once(VEC2_DELIM) {
+            let x = x.trim().parse()?;
+            let y = y.trim().parse()?;
+            Ok(Vec2 { x, y })
+        } else {
+            Err(AocError::ParseStructError(format!(
+                "Missing delimiter '{}' in Vec2 '{}'",
+                VEC2_DELIM, s
+            )))
+        }
+    }
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        try_from = "SerializedGrid<T>",
+        bound(deserialize = "T: serde::Deserialize<'de>")
+    )
+)]
 pub struct Grid<T> {
     num_columns: usize,
     data: Vec<T>,
 }
 
+// deserialized grids are validated the same way as grids created with from_slice
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SerializedGrid<T> {
+    num_columns: usize,
+    data: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<SerializedGrid<T>> for Grid<T> {
+    type Error = AocError;
+
+    fn try_from(value: SerializedGrid<T>) -> Result<Self, Self::Error> {
+        if value.num_columns == 0 {
+            return Err(AocError::GridError(
+                "Can't create a grid without columns".to_string(),
+            ));
+        }
+        if !value.data.len().is_multiple_of(value.num_columns) {
+            return Err(AocError::GridError(format!(
+                "Can't divide {} elements in {} columns",
+                value.data.len(),
+                value.num_columns
+            )));
+        }
+
+        Ok(Grid {
+            num_columns: value.num_columns,
+            data: value.data,
+        })
+    }
+}
+
 // TODO Maybe reimplement Hash only on x and y?
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub struct Point<'a, T> {
@@ -105,6 +332,46 @@ impl<T> Grid<T> {
             data: Vec::new(),
         }
     }
+
+    // like new, but with space for expected_rows rows to be added without reallocating
+    pub fn with_capacity(num_columns: usize, expected_rows: usize) -> Self {
+        Grid {
+            num_columns,
+            data: Vec::with_capacity(num_columns * expected_rows),
+        }
+    }
+
+    pub fn reserve_rows(&mut self, rows: usize) {
+        self.data.reserve(self.num_columns * rows)
+    }
+
+    // the first row determines the number of columns, all other rows have to match it
+    pub fn from_nested(rows: Vec<Vec<T>>) -> AocResult<Self> {
+        let num_columns = rows
+            .first()
+            .map(|row| row.len())
+            .ok_or_else(|| AocError::GridError("Can't create a grid without rows".to_string()))?;
+        if num_columns == 0 {
+            return Err(AocError::GridError(
+                "Can't create a grid without columns".to_string(),
+            ));
+        }
+
+        let mut data = Vec::with_capacity(num_columns * rows.len());
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != num_columns {
+                return Err(AocError::GridError(format!(
+                    "Row {} has {} columns, expected {}",
+                    y,
+                    row.len(),
+                    num_columns
+                )));
+            }
+            data.extend(row);
+        }
+
+        Ok(Grid { num_columns, data })
+    }
 }
 
 impl<T: Default + Clone> Grid<T> {
@@ -125,7 +392,12 @@ impl<T: Clone> Grid<T> {
     }
 
     pub fn from_slice(data: &[T], num_columns: usize) -> AocResult<Self> {
-        if data.len() % num_columns != 0 {
+        if num_columns == 0 {
+            return Err(AocError::GridError(
+                "Can't create a grid without columns".to_string(),
+            ));
+        }
+        if !data.len().is_multiple_of(num_columns) {
             return Err(AocError::GridError(format!(
                 "Can't divide {} elements in {} columns",
                 data.len(),
@@ -146,6 +418,106 @@ impl<T: Clone> Grid<T> {
             data: data.to_vec(),
         }
     }
+
+    // returns a copy of the grid surrounded by a border of fill on all four sides
+    pub fn pad(&self, thickness: usize, fill: T) -> Grid<T> {
+        let num_columns = self.num_columns + 2 * thickness;
+        let mut padded = Grid::with_default(num_columns, self.row_count() + 2 * thickness, fill);
+        for (y, row) in self.data.chunks(self.num_columns).enumerate() {
+            let start = padded.idx(thickness, y + thickness);
+            padded.data[start..start + self.num_columns].clone_from_slice(row);
+        }
+        padded
+    }
+
+    // returns a grid with the columns of other appended to the right of each row
+    pub fn concat_h(&self, other: &Grid<T>) -> AocResult<Grid<T>> {
+        if self.row_count() != other.row_count() {
+            return Err(AocError::GridError(format!(
+                "Horizontally concatenated grids must have the same number of rows {}, got {}",
+                self.row_count(),
+                other.row_count()
+            )));
+        }
+
+        let mut data = Vec::with_capacity(self.data.len() + other.data.len());
+        for (left, right) in self
+            .data
+            .chunks(self.num_columns)
+            .zip(other.data.chunks(other.num_columns))
+        {
+            data.extend_from_slice(left);
+            data.extend_from_slice(right);
+        }
+
+        Ok(Grid {
+            num_columns: self.num_columns + other.num_columns,
+            data,
+        })
+    }
+
+    // returns a grid with the rows of other appended below
+    pub fn concat_v(&self, other: &Grid<T>) -> AocResult<Grid<T>> {
+        if self.num_columns != other.num_columns {
+            return Err(AocError::GridError(format!(
+                "Vertically concatenated grids must have the same number of columns {}, got {}",
+                self.num_columns, other.num_columns
+            )));
+        }
+
+        let mut data = Vec::with_capacity(self.data.len() + other.data.len());
+        data.extend_from_slice(&self.data);
+        data.extend_from_slice(&other.data);
+
+        Ok(Grid {
+            num_columns: self.num_columns,
+            data,
+        })
+    }
+
+    pub fn set_row(&mut self, y: usize, data: &[T]) -> AocResult<()> {
+        if y >= self.row_count() {
+            return Err(AocError::GridError(format!(
+                "Row {} out of bounds for grid with {} rows",
+                y,
+                self.row_count()
+            )));
+        }
+        if data.len() != self.num_columns {
+            return Err(AocError::GridError(format!(
+                "Rows must have the same number of columns as the grid {}, got {}",
+                self.num_columns,
+                data.len()
+            )));
+        }
+
+        let start = self.idx(0, y);
+        self.data[start..start + self.num_columns].clone_from_slice(data);
+        Ok(())
+    }
+
+    pub fn set_col(&mut self, x: usize, data: &[T]) -> AocResult<()> {
+        if x >= self.column_count() {
+            return Err(AocError::GridError(format!(
+                "Column {} out of bounds for grid with {} columns",
+                x,
+                self.column_count()
+            )));
+        }
+        if data.len() != self.row_count() {
+            return Err(AocError::GridError(format!(
+                "Columns must have the same number of rows as the grid {}, got {}",
+                self.row_count(),
+                data.len()
+            )));
+        }
+
+        for (y, value) in data.iter().enumerate() {
+            let idx = self.idx(x, y);
+            self.data[idx] = value.clone();
+        }
+        Ok(())
+    }
 }
 
 impl<T> Grid<T>
@@ -186,7 +558,7 @@ impl<T> Grid<T> {
         self.data.len() / self.num_columns
     }
 
-    pub fn get(&self, x: usize, y: usize) -> Option<Point<T>> {
+    pub fn get(&self, x: usize, y: usize) -> Option<Point<'_, T>> {
         if x >= self.column_count() || y >= self.row_count() {
             return None;
         }
@@ -194,7 +566,7 @@ impl<T> Grid<T> {
         self.data.get(idx).map(|value| Point { x, y, value })
     }
 
-    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<MutPoint<T>> {
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<MutPoint<'_, T>> {
         if x >= self.column_count() || y >= self.row_count() {
             return None;
         }
@@ -211,15 +583,41 @@ impl<T> Grid<T> {
         })
     }
 
+    // returns the first point matching the predicate, searching row by row
+    pub fn find<F: Fn(&T) -> bool>(&self, predicate: F) -> Option<Point<'_, T>> {
+        self.data.iter().position(predicate).map(|idx| Point {
+            x: idx % self.num_columns,
+            y: idx / self.num_columns,
+            value: &self.data[idx],
+        })
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &T> {
         self.data.iter()
     }
 
+    // yields (x, y, value) in row-major order
+    pub fn iter_points(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let num_columns = self.num_columns;
+        self.data
+            .iter()
+            .enumerate()
+            .map(move |(idx, value)| (idx % num_columns, idx / num_columns, value))
+    }
+
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
         self.data.iter_mut()
     }
 
-    pub fn iter_row(&self, row: usize) -> GridRowIterator<T> {
+    // calls f with the coordinates and a mutable reference of every cell in row-major order
+    pub fn apply<F: FnMut(usize, usize, &mut T)>(&mut self, mut f: F) {
+        let num_columns = self.num_columns;
+        for (idx, value) in self.data.iter_mut().enumerate() {
+            f(idx % num_columns, idx / num_columns, value)
+        }
+    }
+
+    pub fn iter_row(&self, row: usize) -> GridRowIterator<'_, T> {
         GridRowIterator {
             grid: self,
             row,
@@ -227,7 +625,7 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn iter_col(&self, column: usize) -> GridColumnIterator<T> {
+    pub fn iter_col(&self, column: usize) -> GridColumnIterator<'_, T> {
         GridColumnIterator {
             grid: self,
             column,
@@ -235,11 +633,65 @@ impl<T> Grid<T> {
         }
     }
 
-    pub fn neighbours(&self, x: usize, y: usize) -> [Option<Point<T>>; 4] {
-        let left = if x > 0 { self.get(x - 1, y) } else { None };
-        let up = if y > 0 { self.get(x, y - 1) } else { None };
-        let right = self.get(x + 1, y);
-        let down = self.get(x, y + 1);
+    // walks from start towards the bottom right corner until leaving the grid
+    pub fn iter_diagonal_down_right(
+        &self,
+        start: (usize, usize),
+    ) -> impl Iterator<Item = Point<'_, T>> {
+        self.iter_diagonal(start, 1)
+    }
+
+    // walks from start towards the bottom left corner until leaving the grid
+    pub fn iter_diagonal_down_left(
+        &self,
+        start: (usize, usize),
+    ) -> impl Iterator<Item = Point<'_, T>> {
+        self.iter_diagonal(start, -1)
+    }
+
+    fn iter_diagonal(
+        &self,
+        (x, y): (usize, usize),
+        dx: isize,
+    ) -> impl Iterator<Item = Point<'_, T>> {
+        successors(self.get(x, y), move |p| self.get_offset(p.x, p.y, dx, 1))
+    }
+
+    // returns None if applying the delta leaves the grid on any side
+    pub fn get_offset(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<Point<'_, T>> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+        self.get(x, y)
+    }
+
+    pub fn neighbours(&self, x: usize, y: usize) -> [Option<Point<'_, T>>; 4] {
+        let left = self.get_offset(x, y, -1, 0);
+        let up = self.get_offset(x, y, 0, -1);
+        let right = self.get_offset(x, y, 1, 0);
+        let down = self.get_offset(x, y, 0, 1);
+        [left, up, right, down]
+    }
+
+    // values of the in-bounds neighbours, without their coordinates
+    pub fn neighbour_values(&self, x: usize, y: usize) -> impl Iterator<Item = &T> {
+        self.neighbours(x, y).into_iter().flatten().map(|p| p.value)
+    }
+
+    // like neighbours, but leaving the grid on one edge enters it again on the opposite edge,
+    // such that every cell has exactly four neighbours
+    pub fn neighbours_wrapping(&self, x: usize, y: usize) -> [Point<'_, T>; 4] {
+        let (columns, rows) = self.dimensions();
+        let (x, y) = (x % columns, y % rows);
+        let point = |x, y| Point {
+            x,
+            y,
+            value: &self.data[self.idx(x, y)],
+        };
+
+        let left = point((x + columns - 1) % columns, y);
+        let up = point(x, (y + rows - 1) % rows);
+        let right = point((x + 1) % columns, y);
+        let down = point(x, (y + 1) % rows);
         [left, up, right, down]
     }
 
@@ -250,26 +702,126 @@ impl<T> Grid<T> {
             .filter_map(|p| p.as_ref().map(|pp| (pp.x, pp.y)))
             .collect();
 
-        if x > 0 && y > 0 {
-            if let Some(left_up) = self.get(x - 1, y - 1) {
-                surrounding_nodes.push((left_up.x, left_up.y))
+        for (dx, dy) in [(-1, -1), (1, -1), (1, 1), (-1, 1)] {
+            if let Some(diagonal) = self.get_offset(x, y, dx, dy) {
+                surrounding_nodes.push((diagonal.x, diagonal.y))
             }
         }
-        if y > 0 {
-            if let Some(up_right) = self.get(x + 1, y - 1) {
-                surrounding_nodes.push((up_right.x, up_right.y));
-            }
+
+        surrounding_nodes
+    }
+
+    // coordinates of all points connected to (x, y) through neighbours matching the predicate,
+    // in the order they are reached; empty if (x, y) itself does not match
+    pub fn flood_fill<F: Fn(&T) -> bool>(
+        &self,
+        x: usize,
+        y: usize,
+        predicate: F,
+    ) -> Vec<(usize, usize)> {
+        self.flood_fill_with(x, y, predicate, |x, y| {
+            self.neighbours(x, y)
+                .iter()
+                .flatten()
+                .map(|p| (p.x, p.y))
+                .collect()
+        })
+    }
+
+    // same as flood_fill, but diagonal points are connected as well
+    pub fn flood_fill_surrounding<F: Fn(&T) -> bool>(
+        &self,
+        x: usize,
+        y: usize,
+        predicate: F,
+    ) -> Vec<(usize, usize)> {
+        self.flood_fill_with(x, y, predicate, |x, y| self.surrounding_indexes(x, y))
+    }
+
+    fn flood_fill_with<F, N>(
+        &self,
+        x: usize,
+        y: usize,
+        predicate: F,
+        connected: N,
+    ) -> Vec<(usize, usize)>
+    where
+        F: Fn(&T) -> bool,
+        N: Fn(usize, usize) -> Vec<(usize, usize)>,
+    {
+        let mut filled = Vec::new();
+        match self.get(x, y) {
+            Some(start) if predicate(start.value) => {}
+            _ => return filled,
         }
-        if let Some(right_down) = self.get(x + 1, y + 1) {
-            surrounding_nodes.push((right_down.x, right_down.y));
-        };
-        if x > 0 {
-            if let Some(down_left) = self.get(x - 1, y + 1) {
-                surrounding_nodes.push((down_left.x, down_left.y))
+
+        let mut queue = VecDeque::from([(x, y)]);
+        let mut visited = HashSet::from([(x, y)]);
+        while let Some((x, y)) = queue.pop_front() {
+            filled.push((x, y));
+            for (nx, ny) in connected(x, y) {
+                let matches = self.get(nx, ny).is_some_and(|p| predicate(p.value));
+                if matches && visited.insert((nx, ny)) {
+                    queue.push_back((nx, ny));
+                }
             }
         }
+        filled
+    }
+}
 
-        surrounding_nodes
+impl Grid<u32> {
+    // One step of a cellular automaton like the dumbo octopuses: every value increases by 1, then
+    // every value above threshold flashes, which resets it to 0 and increases its neighbours.
+    // This cascades until no value is above threshold, values that flashed stay at 0.
+    // Returns the number of flashes during the step.
+    pub fn step_threshold(&mut self, threshold: u32, neighbours_8: bool) -> usize {
+        self.step_threshold_points(threshold, neighbours_8).len()
+    }
+
+    // same as step_threshold, but returns the coordinates that flashed, in the order they flashed
+    pub fn step_threshold_points(
+        &mut self,
+        threshold: u32,
+        neighbours_8: bool,
+    ) -> Vec<(usize, usize)> {
+        for value in self.data.iter_mut() {
+            *value += 1
+        }
+
+        let mut flashing: Vec<_> = self
+            .iter_points()
+            .filter(|(_, _, &value)| value > threshold)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let mut flashes = Vec::new();
+        while let Some((x, y)) = flashing.pop() {
+            flashes.push((x, y));
+            let idx = self.idx(x, y);
+            self.data[idx] = 0;
+
+            let connected = if neighbours_8 {
+                self.surrounding_indexes(x, y)
+            } else {
+                self.neighbours(x, y)
+                    .iter()
+                    .flatten()
+                    .map(|p| (p.x, p.y))
+                    .collect()
+            };
+            for (x, y) in connected {
+                let idx = self.idx(x, y);
+                let value = &mut self.data[idx];
+                // values of 0 already flashed during this step
+                if *value > 0 && *value <= threshold {
+                    *value += 1;
+                    if *value > threshold {
+                        flashing.push((x, y))
+                    }
+                }
+            }
+        }
+        flashes
     }
 }
 
@@ -322,6 +874,7 @@ mod tests {
         let data = [1, 2, 3, 4];
 
         let failed_grid = Grid::from_slice(&data, 3);
+        let no_columns = Grid::<u32>::from_slice(&[], 0);
         let grid = Grid::from_slice(&data, 2).unwrap();
 
         assert_eq!(
@@ -360,7 +913,33 @@ mod tests {
         assert_eq!(grid.get(2, 0), None);
         assert_eq!(grid.get(0, 2), None);
 
-        assert!(failed_grid.is_err())
+        assert!(failed_grid.is_err());
+        assert!(matches!(no_columns, Err(AocError::GridError(_))));
+    }
+
+    #[test]
+    fn grid_from_nested() {
+        let grid = Grid::from_nested(vec![vec![1, 2], vec![3, 4], vec![5, 6]]).unwrap();
+        let ragged = Grid::from_nested(vec![vec![1, 2], vec![3], vec![5, 6]]);
+        let empty = Grid::<u32>::from_nested(vec![]);
+
+        assert_eq!(grid.dimensions(), (2, 3));
+        assert_eq!(grid.data, [1, 2, 3, 4, 5, 6]);
+        assert!(ragged.is_err());
+        assert!(empty.is_err());
+    }
+
+    #[test]
+    fn grid_with_capacity() {
+        let mut grid = Grid::with_capacity(3, 4);
+        let capacity_before = grid.data.capacity();
+
+        grid.add_row(&[1, 2, 3]).unwrap();
+        grid.reserve_rows(10);
+
+        assert!(capacity_before >= 12);
+        assert!(grid.data.capacity() >= 33);
+        assert_eq!(grid.dimensions(), (3, 1));
     }
 
     #[test]
@@ -405,6 +984,97 @@ mod tests {
         assert_eq!(grid.data, [1, 0, 42, 0, 100, 0, 23, 0, 3])
     }
 
+    #[test]
+    fn grid_set_row_and_col() {
+        let mut grid = Grid::with_default(3, 2, 0_u32);
+
+        grid.set_row(1, &[4, 5, 6]).unwrap();
+        grid.set_col(0, &[7, 8]).unwrap();
+        let row_too_short = grid.set_row(0, &[1, 2]);
+        let col_too_long = grid.set_col(1, &[1, 2, 3]);
+        let row_out_of_range = grid.set_row(2, &[1, 2, 3]);
+        let col_out_of_range = grid.set_col(3, &[1, 2]);
+
+        assert_eq!(grid.data, [7, 0, 0, 8, 5, 6]);
+        assert!(row_too_short.is_err());
+        assert!(col_too_long.is_err());
+        assert!(row_out_of_range.is_err());
+        assert!(col_out_of_range.is_err());
+    }
+
+    #[test]
+    fn grid_pad() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4], 2).unwrap();
+
+        let padded = grid.pad(1, 0);
+
+        assert_eq!(padded.dimensions(), (4, 4));
+        assert_eq!(
+            padded.data,
+            [0, 0, 0, 0, 0, 1, 2, 0, 0, 3, 4, 0, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn grid_concat() {
+        let left = Grid::from_slice(&[1, 2, 3, 4], 2).unwrap();
+        let right = Grid::from_slice(&[5, 6, 7, 8], 2).unwrap();
+        let single_row = Grid::from_slice(&[9, 9, 9], 3).unwrap();
+
+        let horizontal = left.concat_h(&right).unwrap();
+        let vertical = left.concat_v(&right).unwrap();
+        let horizontal_mismatch = left.concat_h(&single_row);
+        let vertical_mismatch = left.concat_v(&single_row);
+
+        assert_eq!(horizontal.dimensions(), (4, 2));
+        assert_eq!(horizontal.data, [1, 2, 5, 6, 3, 4, 7, 8]);
+        assert_eq!(vertical.dimensions(), (2, 4));
+        assert_eq!(vertical.data, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert!(horizontal_mismatch.is_err());
+        assert!(vertical_mismatch.is_err());
+    }
+
+    #[test]
+    fn grid_find() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
+
+        let found = grid.find(|&n| n == 6);
+        let first_even = grid.find(|&n| n % 2 == 0);
+        let missing = grid.find(|&n| n > 9);
+
+        assert_eq!(
+            found,
+            Some(Point {
+                x: 2,
+                y: 1,
+                value: &6
+            })
+        );
+        assert_eq!(first_even.map(|p| (p.x, p.y)), Some((1, 0)));
+        assert_eq!(missing, None);
+    }
+
+    #[test]
+    fn test_points_iterator() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4], 2).unwrap();
+
+        let points: Vec<_> = grid.iter_points().collect();
+
+        assert_eq!(points, [(0, 0, &1), (1, 0, &2), (0, 1, &3), (1, 1, &4)]);
+    }
+
+    #[test]
+    fn grid_apply() {
+        let mut doubled = Grid::from_slice(&[1, 2, 3, 4, 5, 6], 3).unwrap();
+        let mut coordinates = Grid::with_default(3, 2, 0);
+
+        doubled.apply(|_, _, value| *value *= 2);
+        coordinates.apply(|x, y, value| *value = x + y);
+
+        assert_eq!(doubled.data, [2, 4, 6, 8, 10, 12]);
+        assert_eq!(coordinates.data, [0, 1, 2, 1, 2, 3]);
+    }
+
     #[test]
     fn test_row_iterator() {
         let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
@@ -455,10 +1125,271 @@ mod tests {
         assert_eq!(third_col.next(), None);
     }
 
+    #[test]
+    fn grid_get_offset() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
+
+        let in_bounds = grid.get_offset(1, 1, 1, -1);
+        let zero_delta = grid.get_offset(2, 2, 0, 0);
+        let off_top = grid.get_offset(1, 0, 0, -1);
+        let off_right = grid.get_offset(2, 1, 1, 0);
+
+        assert_eq!(
+            in_bounds,
+            Some(Point {
+                x: 2,
+                y: 0,
+                value: &3
+            })
+        );
+        assert_eq!(zero_delta.map(|p| p.value), Some(&9));
+        assert_eq!(off_top, None);
+        assert_eq!(off_right, None);
+    }
+
+    #[test]
+    fn test_neighbour_values() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
+
+        let corner: Vec<_> = grid.neighbour_values(0, 0).collect();
+        let bottom_right: Vec<_> = grid.neighbour_values(2, 2).collect();
+        let interior: Vec<_> = grid.neighbour_values(1, 1).collect();
+
+        assert_eq!(corner, [&2, &4]);
+        assert_eq!(bottom_right, [&8, &6]);
+        assert_eq!(interior, [&4, &2, &6, &8]);
+    }
+
+    #[test]
+    fn test_diagonal_iterators() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
+
+        let main_diagonal: Vec<_> = grid
+            .iter_diagonal_down_right((0, 0))
+            .map(|p| (p.x, p.y))
+            .collect();
+        let anti_diagonal: Vec<_> = grid
+            .iter_diagonal_down_left((2, 0))
+            .map(|p| *p.value)
+            .collect();
+        let shortened: Vec<_> = grid
+            .iter_diagonal_down_right((1, 0))
+            .map(|p| *p.value)
+            .collect();
+        let outside = grid.iter_diagonal_down_right((3, 0)).count();
+
+        assert_eq!(main_diagonal, [(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(anti_diagonal, [3, 5, 7]);
+        assert_eq!(shortened, [2, 6]);
+        assert_eq!(outside, 0);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        #[rustfmt::skip]
+        let grid = Grid::from_slice(&[
+            1, 1, 0,
+            0, 1, 0,
+            1, 0, 1,
+        ], 3).unwrap();
+
+        let filled = grid.flood_fill(0, 0, |&v| v == 1);
+        let corner = grid.flood_fill(2, 2, |&v| v == 1);
+        let not_matching = grid.flood_fill(2, 0, |&v| v == 1);
+
+        let filled_surrounding = grid.flood_fill_surrounding(0, 0, |&v| v == 1);
+
+        assert_eq!(filled, [(0, 0), (1, 0), (1, 1)]);
+        assert_eq!(corner, [(2, 2)]);
+        assert_eq!(filled_surrounding.len(), 5);
+        assert!(not_matching.is_empty());
+    }
+
+    #[test]
+    fn test_step_threshold() {
+        #[rustfmt::skip]
+        let mut grid = Grid::from_slice(&[
+            0, 2, 0,
+            2, 3, 2,
+            0, 2, 0,
+        ], 3).unwrap();
+        let mut diagonal = grid.clone();
+
+        let flashes = grid.step_threshold(3, false);
+        let diagonal_flashes = diagonal.step_threshold(3, true);
+
+        // the middle makes its direct neighbours flash, but the corners only reach 3
+        assert_eq!(flashes, 5);
+        assert_eq!(
+            grid.iter().copied().collect::<Vec<_>>(),
+            [3, 0, 3, 0, 0, 0, 3, 0, 3]
+        );
+        assert_eq!(diagonal_flashes, 9);
+        assert!(diagonal.iter().all(|&v| v == 0));
+    }
+
+    #[test]
+    fn test_neighbours_wrapping() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6, 7, 8, 9], 3).unwrap();
+
+        let [left, up, right, down] = grid.neighbours_wrapping(0, 0);
+        let [center_left, center_up, center_right, center_down] = grid.neighbours_wrapping(1, 1);
+
+        // the top left cell wraps around to the rightmost column and the bottom row
+        assert_eq!(
+            left,
+            Point {
+                x: 2,
+                y: 0,
+                value: &3
+            }
+        );
+        assert_eq!(
+            up,
+            Point {
+                x: 0,
+                y: 2,
+                value: &7
+            }
+        );
+        assert_eq!(*right.value, 2);
+        assert_eq!(*down.value, 4);
+
+        assert_eq!(*center_left.value, 4);
+        assert_eq!(*center_up.value, 2);
+        assert_eq!(*center_right.value, 6);
+        assert_eq!(*center_down.value, 8);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn grid_serde_round_trip() {
+        let grid = Grid::from_slice(&[1, 2, 3, 4, 5, 6], 2).unwrap();
+
+        let json = serde_json::to_string(&grid).unwrap();
+        let deserialized: Grid<u32> = serde_json::from_str(&json).unwrap();
+        let uneven: Result<Grid<u32>, _> =
+            serde_json::from_str(r#"{"num_columns":2,"data":[1,2,3]}"#);
+        let no_columns: Result<Grid<u32>, _> =
+            serde_json::from_str(r#"{"num_columns":0,"data":[]}"#);
+
+        assert_eq!(json, r#"{"num_columns":2,"data":[1,2,3,4,5,6]}"#);
+        assert_eq!(deserialized.dimensions(), (2, 3));
+        assert_eq!(deserialized.data, grid.data);
+        assert!(uneven.is_err());
+        assert!(no_columns.is_err());
+    }
+
+    #[test]
+    fn error_shows_cause() {
+        let error = read_file_string("does_not_exist.input").unwrap_err();
+        let parse_error = AocError::from("x".parse::<u32>().unwrap_err());
+
+        let message = error.to_string();
+        match &error {
+            AocError::IoError(io_error) => {
+                assert!(message.starts_with("io error: "));
+                assert!(message.contains(&io_error.to_string()));
+            }
+            _ => panic!("expected io error, got {:?}", error),
+        }
+        assert!(std::error::Error::source(&error).is_some());
+        assert_eq!(
+            parse_error.to_string(),
+            "parse int error: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn vec2_parse() {
+        let parsed: Vec2<u32> = "3,4".parse().unwrap();
+        let negative: Vec2<i32> = "-3,-4".parse().unwrap();
+        let missing_delimiter = "3 4".parse::<Vec2<u32>>();
+        let negative_unsigned = "-3,4".parse::<Vec2<u32>>();
+
+        assert_eq!(parsed, Vec2::new(3, 4));
+        assert_eq!(negative, Vec2 { x: -3, y: -4 });
+        assert!(missing_delimiter.is_err());
+        assert!(negative_unsigned.is_err());
+    }
+
+    #[test]
+    fn vec2_add_sub() {
+        let a = Vec2::new(3, 4);
+        let b = Vec2::new(1, -6);
+
+        assert_eq!(a + b, Vec2::new(4, -2));
+        assert_eq!(a - b, Vec2::new(2, 10));
+    }
+
+    #[test]
+    fn test_time_it() {
+        let (result, duration) = time_it(|| (1..=10).sum::<u32>());
+
+        assert_eq!(result, 55);
+        assert!(duration >= Duration::ZERO);
+    }
+
+    #[test]
+    fn test_input_path() {
+        let path = input_path("helpers", "readline_numbers.input");
+
+        assert!(path.is_absolute());
+        assert!(path.exists());
+    }
+
     #[test]
     fn test_read_file_numbers() {
         let lines: Vec<usize> = read_lines_parse("readline_numbers.input").unwrap();
 
         assert_eq!(&lines, &[0, 1, 2, 3]);
     }
+
+    #[test]
+    fn test_parse_lines_reader() {
+        let input = io::Cursor::new("7\n8\n9");
+        let invalid = io::Cursor::new("7\neight");
+
+        let parsed: AocResult<Vec<u32>> = parse_lines(input).collect();
+        let failed: AocResult<Vec<u32>> = parse_lines(invalid).collect();
+        let string = read_string(io::Cursor::new("first\nsecond\n")).unwrap();
+
+        assert_eq!(parsed.unwrap(), [7, 8, 9]);
+        assert!(failed.is_err());
+        assert_eq!(string, "first\nsecond\n");
+    }
+
+    #[test]
+    fn test_parse_lines_iter() {
+        let numbers = parse_lines_iter::<usize, _>("readline_numbers.input").unwrap();
+        let sum: AocResult<usize> = numbers.sum();
+
+        assert_eq!(sum.unwrap(), 6);
+    }
+
+    #[test]
+    fn test_parse_tokens() {
+        let parsed: Vec<u32> = parse_tokens("1 2  3\n4").unwrap();
+        let failed = parse_tokens::<u32>("1 two 3");
+
+        assert_eq!(parsed, vec![1, 2, 3, 4]);
+        assert!(matches!(failed, Err(AocError::ParseIntError(_))));
+    }
+
+    #[test]
+    fn test_read_blocks() {
+        let blocks = read_blocks("blocks.input").unwrap();
+
+        assert_eq!(
+            blocks,
+            ["first block\nline two", "second block", "third\nblock"]
+        );
+    }
+
+    #[test]
+    fn test_read_csv_numbers() {
+        let numbers: Vec<u32> = read_csv_numbers("csv_numbers.input").unwrap();
+
+        assert_eq!(&numbers, &[3, 4, 3, 1, 2]);
+    }
 }